@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::TARGET_SAMPLE_RATE;
+
+/// Write a 16 kHz mono PCM recording to `dir` as a timestamped WAV file.
+pub fn save_wav(dir: &Path, pcm: &[i16]) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+    let name = chrono::Local::now().format("fluxvoice_%Y%m%d_%H%M%S_%3f.wav").to_string();
+    let path = dir.join(name);
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec)
+        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+    for sample in pcm {
+        writer
+            .write_sample(*sample)
+            .map_err(|e| format!("Failed to write WAV file: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finish WAV file: {}", e))?;
+
+    log::info!("Saved lossless copy: {} ({} samples)", path.display(), pcm.len());
+    println!(">>> Saved lossless copy: {}", path.display());
+    Ok(path)
+}
+
+/// Delete the oldest WAV files in `dir` until they take up at most `max_bytes`.
+/// Returns the deleted files, so links to them can be dropped.
+pub fn enforce_retention(dir: &Path, max_bytes: u64) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read recordings directory: {}", e))?;
+
+    let mut files: Vec<(PathBuf, u64)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+        .filter_map(|path| fs::metadata(&path).ok().map(|m| (path, m.len())))
+        .collect();
+
+    // Names start with the recording time, so sorting by name puts the oldest first
+    files.sort();
+
+    let mut total: u64 = files.iter().map(|(_, size)| size).sum();
+    let mut deleted = Vec::new();
+    for (path, size) in files {
+        if total <= max_bytes {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                log::info!("Retention: deleted {}", path.display());
+                total -= size;
+                deleted.push(path);
+            }
+            Err(e) => log::warn!("Retention: failed to delete {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("fluxvoice_archive_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn write(&self, name: &str, bytes: usize) {
+            fs::write(self.0.join(name), vec![0u8; bytes]).unwrap();
+        }
+
+        fn names(&self) -> Vec<String> {
+            let mut names: Vec<String> = fs::read_dir(&self.0)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn save_wav_writes_16khz_mono_pcm() {
+        let dir = TempDir::new("save");
+        let pcm: Vec<i16> = (0..1600).map(|i| (i * 10) as i16).collect();
+        let path = save_wav(&dir.0.join("recordings"), &pcm).unwrap();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, TARGET_SAMPLE_RATE);
+        assert_eq!(reader.spec().channels, 1);
+        let samples: Vec<i16> = reader.samples().map(Result::unwrap).collect();
+        assert_eq!(samples, pcm);
+    }
+
+    #[test]
+    fn deletes_oldest_recordings_first() {
+        let dir = TempDir::new("order");
+        // Written out of order, so only the names give the age away
+        dir.write("fluxvoice_20240103_090000_000.wav", 100);
+        dir.write("fluxvoice_20240101_090000_000.wav", 100);
+        dir.write("fluxvoice_20240102_090000_000.wav", 100);
+
+        let deleted = enforce_retention(&dir.0, 150).unwrap();
+        assert_eq!(
+            deleted,
+            [dir.0.join("fluxvoice_20240101_090000_000.wav"), dir.0.join("fluxvoice_20240102_090000_000.wav")]
+        );
+        assert_eq!(dir.names(), ["fluxvoice_20240103_090000_000.wav"]);
+    }
+
+    #[test]
+    fn stays_within_the_byte_budget() {
+        let dir = TempDir::new("budget");
+        for (day, size) in [(1, 300), (2, 200), (3, 250), (4, 100)] {
+            dir.write(&format!("fluxvoice_2024010{}_090000_000.wav", day), size);
+        }
+
+        // 850 bytes in total: dropping the two oldest gets under 400
+        assert_eq!(enforce_retention(&dir.0, 400).unwrap().len(), 2);
+        assert_eq!(dir.names(), ["fluxvoice_20240103_090000_000.wav", "fluxvoice_20240104_090000_000.wav"]);
+
+        // Already within the budget
+        assert!(enforce_retention(&dir.0, 350).unwrap().is_empty());
+        assert_eq!(dir.names().len(), 2);
+    }
+
+    #[test]
+    fn leaves_other_files_alone() {
+        let dir = TempDir::new("other");
+        dir.write("fluxvoice_20240101_090000_000.wav", 100);
+        dir.write("aaa_notes.txt", 1000);
+        dir.write("fluxvoice_20240101_080000_000.ogg", 1000);
+
+        enforce_retention(&dir.0, 0).unwrap();
+        assert_eq!(dir.names(), ["aaa_notes.txt", "fluxvoice_20240101_080000_000.ogg"]);
+    }
+}
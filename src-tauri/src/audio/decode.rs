@@ -0,0 +1,200 @@
+use audiopus::{coder::Decoder, Channels, SampleRate};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+// Opus always decodes at 48 kHz
+const OPUS_SAMPLE_RATE: u32 = 48000;
+// Longest Opus packet is 120ms
+const MAX_OPUS_FRAME_SAMPLES: usize = 5760;
+
+/// A whole audio file decoded to interleaved f32 samples.
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl DecodedAudio {
+    pub fn duration_secs(&self) -> f32 {
+        let frames = self.samples.len() / self.channels.max(1) as usize;
+        frames as f32 / self.sample_rate.max(1) as f32
+    }
+}
+
+/// Decode a WAV, FLAC, MP3 or OGG (Opus or Vorbis) file.
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, String> {
+    let mut data = Vec::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let extension = path.extension().and_then(|e| e.to_str());
+    let decoded = decode_bytes(data, extension)?;
+
+    log::info!(
+        "Decoded {}: {} Hz, {} channels, {:.1}s",
+        path.display(),
+        decoded.sample_rate,
+        decoded.channels,
+        decoded.duration_secs()
+    );
+    println!(
+        ">>> Decoded {}: {} Hz, {} channels, {:.1}s",
+        path.display(),
+        decoded.sample_rate,
+        decoded.channels,
+        decoded.duration_secs()
+    );
+
+    if decoded.samples.is_empty() {
+        return Err(format!("No audio found in {}", path.display()));
+    }
+    Ok(decoded)
+}
+
+/// Decode audio held in memory. `extension` helps guess the container.
+pub fn decode_bytes(data: Vec<u8>, extension: Option<&str>) -> Result<DecodedAudio, String> {
+    // Symphonia has no Opus decoder, so Ogg Opus goes through libopus directly
+    if is_ogg_opus(&data) {
+        decode_ogg_opus(&data)
+    } else {
+        decode_with_symphonia(data, extension)
+    }
+}
+
+fn is_ogg_opus(data: &[u8]) -> bool {
+    // The first page of an Ogg Opus stream carries only the OpusHead packet
+    data.starts_with(b"OggS") && data.windows(8).take(64).any(|w| w == b"OpusHead")
+}
+
+fn decode_with_symphonia(data: Vec<u8>, extension: Option<&str>) -> Result<DecodedAudio, String> {
+    let stream = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Unsupported audio format: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("No audio track found")?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track.codec_params.channels.map(|c| c.count() as u16).unwrap_or(0);
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported audio codec: {}", e))?;
+
+    let mut samples = Vec::new();
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            // End of stream
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = spec.rate;
+                channels = spec.channels.count() as u16;
+
+                // Reuse the conversion buffer unless this packet is larger
+                let needed = decoded.capacity() * spec.channels.count();
+                if !matches!(buffer, Some(ref buf) if buf.capacity() >= needed) {
+                    buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+                }
+                if let Some(ref mut buf) = buffer {
+                    buf.copy_interleaved_ref(decoded);
+                    samples.extend_from_slice(buf.samples());
+                }
+            }
+            // Skip corrupt packets rather than failing the whole file
+            Err(SymphoniaError::DecodeError(e)) => log::warn!("Skipping undecodable packet: {}", e),
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        }
+    }
+
+    Ok(DecodedAudio { samples, sample_rate, channels })
+}
+
+fn decode_ogg_opus(data: &[u8]) -> Result<DecodedAudio, String> {
+    let mut reader = ogg::reading::PacketReader::new(Cursor::new(data));
+
+    let head = reader
+        .read_packet()
+        .map_err(|e| format!("Failed to read Ogg stream: {}", e))?
+        .ok_or("Empty Ogg stream")?;
+    if head.data.len() < 19 || !head.data.starts_with(b"OpusHead") {
+        return Err("Missing OpusHead header".to_string());
+    }
+    let channels = head.data[9];
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    let opus_channels = match channels {
+        1 => Channels::Mono,
+        2 => Channels::Stereo,
+        n => return Err(format!("Unsupported Opus channel count: {}", n)),
+    };
+
+    let mut decoder = Decoder::new(SampleRate::Hz48000, opus_channels)
+        .map_err(|e| format!("Failed to create Opus decoder: {:?}", e))?;
+
+    let mut samples = Vec::new();
+    let mut frame = vec![0.0f32; MAX_OPUS_FRAME_SAMPLES * channels as usize];
+    // The OpusTags packet follows the header and carries no audio
+    let mut skipped_tags = false;
+    // Granule position of the final page, which marks where the audio really ends
+    let mut end_granule = None;
+
+    while let Some(packet) = reader
+        .read_packet()
+        .map_err(|e| format!("Failed to read Ogg stream: {}", e))?
+    {
+        if !skipped_tags {
+            skipped_tags = true;
+            continue;
+        }
+        let decoded = decoder
+            .decode_float(Some(&packet.data[..]), &mut frame[..], false)
+            .map_err(|e| format!("Failed to decode Opus packet: {:?}", e))?;
+        samples.extend_from_slice(&frame[..decoded * channels as usize]);
+        if packet.last_in_stream() {
+            end_granule = Some(packet.absgp_page() as usize);
+        }
+    }
+
+    // Drop the padding after the end granule. Streams that don't start at
+    // granule 0 would cut far more than one frame, leave those alone.
+    let decoded_frames = samples.len() / channels as usize;
+    if let Some(end) = end_granule.filter(|&end| end <= decoded_frames && decoded_frames - end < MAX_OPUS_FRAME_SAMPLES) {
+        samples.truncate(end * channels as usize);
+    }
+
+    // Drop the encoder delay announced in the header
+    let skip = (pre_skip * channels as usize).min(samples.len());
+    samples.drain(..skip);
+
+    Ok(DecodedAudio { samples, sample_rate: OPUS_SAMPLE_RATE, channels: channels as u16 })
+}
@@ -0,0 +1,292 @@
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use super::vad;
+
+// 32ms analysis frames with 50% overlap at 16 kHz
+const FRAME_SIZE: usize = 512;
+const HOP_SIZE: usize = FRAME_SIZE / 2;
+// Frames used for the initial noise estimate (~250ms)
+const NOISE_INIT_FRAMES: usize = 16;
+// How quickly the noise estimate follows quieter bins, and creeps up towards louder ones
+const NOISE_ATTACK: f32 = 0.1;
+const NOISE_RELEASE: f32 = 0.002;
+// Gain smoothing over time, reduces "musical noise" artifacts
+const GAIN_SMOOTHING: f32 = 0.6;
+// Maximum attenuation at full strength
+const MAX_REDUCTION_DB: f32 = 30.0;
+
+/// Streaming spectral-gating noise suppressor for 16 kHz mono audio.
+///
+/// Tracks a per-bin noise magnitude and attenuates bins that don't rise
+/// clearly above it. `strength` (0.0 - 1.0) sets both how aggressively the
+/// noise is subtracted and how far gated bins are pulled down. Output has the
+/// same length as the input.
+pub struct Denoiser {
+    forward: Arc<dyn RealToComplex<f32>>,
+    inverse: Arc<dyn ComplexToReal<f32>>,
+    window: Vec<f32>,
+    over_subtraction: f32,
+    gain_floor: f32,
+    noise: Vec<f32>,
+    noise_frames: usize,
+    gains: Vec<f32>,
+    // Last FRAME_SIZE input samples, and the overlap-add accumulator
+    input: Vec<f32>,
+    overlap: Vec<f32>,
+    pending: Vec<f32>,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    // Output still to drop to compensate for the frame delay
+    latency: usize,
+    input_count: u64,
+    output_count: u64,
+    meter_in: SnrMeter,
+    meter_out: SnrMeter,
+}
+
+impl Denoiser {
+    pub fn new(strength: f32) -> Self {
+        let strength = strength.clamp(0.0, 1.0);
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(FRAME_SIZE);
+        let inverse = planner.plan_fft_inverse(FRAME_SIZE);
+        let spectrum = forward.make_output_vec();
+        let bins = spectrum.len();
+
+        // sqrt-Hann for both analysis and synthesis sums to one at 50% overlap
+        let window = (0..FRAME_SIZE)
+            .map(|i| (0.5 - 0.5 * (2.0 * PI * i as f32 / FRAME_SIZE as f32).cos()).sqrt())
+            .collect();
+
+        Self {
+            forward,
+            inverse,
+            window,
+            over_subtraction: 1.0 + 2.0 * strength,
+            gain_floor: 10f32.powf(-MAX_REDUCTION_DB * strength / 20.0),
+            noise: vec![0.0; bins],
+            noise_frames: 0,
+            gains: vec![1.0; bins],
+            input: vec![0.0; FRAME_SIZE],
+            overlap: vec![0.0; FRAME_SIZE],
+            pending: Vec::with_capacity(HOP_SIZE),
+            time: vec![0.0; FRAME_SIZE],
+            spectrum,
+            latency: FRAME_SIZE - HOP_SIZE,
+            input_count: 0,
+            output_count: 0,
+            meter_in: SnrMeter::default(),
+            meter_out: SnrMeter::default(),
+        }
+    }
+
+    /// Denoise `input`, appending whatever output is ready to `output`.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        self.input_count += input.len() as u64;
+        self.meter_in.push(input);
+
+        let start = output.len();
+        for &sample in input {
+            self.pending.push(sample);
+            if self.pending.len() == HOP_SIZE {
+                self.process_hop(output);
+            }
+        }
+        self.meter_out.push(&output[start..]);
+    }
+
+    /// Emit the remaining output, treating everything after the last input as silence.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        let start = output.len();
+        while self.output_count < self.input_count {
+            self.pending.resize(HOP_SIZE, 0.0);
+            self.process_hop(output);
+        }
+        // The last hop may run past the end of the input
+        let excess = (self.output_count - self.input_count) as usize;
+        output.truncate(output.len() - excess.min(output.len() - start));
+        self.output_count = self.input_count;
+        self.meter_out.push(&output[start..]);
+    }
+
+    /// Log the estimated SNR of the audio before and after suppression.
+    pub fn log_snr(&self) {
+        match (self.meter_in.snr_db(), self.meter_out.snr_db()) {
+            (Some(before), Some(after)) => {
+                log::info!("Noise suppression: SNR {:.1} dB before, {:.1} dB after", before, after);
+                println!(">>> Noise suppression: SNR {:.1} dB before, {:.1} dB after", before, after);
+            }
+            _ => log::info!("Noise suppression: not enough audio to estimate SNR"),
+        }
+    }
+
+    fn process_hop(&mut self, output: &mut Vec<f32>) {
+        self.input.copy_within(HOP_SIZE.., 0);
+        self.input[FRAME_SIZE - HOP_SIZE..].copy_from_slice(&self.pending);
+        self.pending.clear();
+
+        for (t, (x, w)) in self.time.iter_mut().zip(self.input.iter().zip(&self.window)) {
+            *t = x * w;
+        }
+        // Buffer sizes come from the FFT plans, so neither transform can fail on length
+        let _ = self.forward.process(&mut self.time, &mut self.spectrum);
+
+        self.update_gains();
+        for (bin, gain) in self.spectrum.iter_mut().zip(&self.gains) {
+            *bin *= *gain;
+        }
+
+        let _ = self.inverse.process(&mut self.spectrum, &mut self.time);
+
+        // Synthesis window, undo the unnormalized inverse FFT and overlap-add
+        let scale = 1.0 / FRAME_SIZE as f32;
+        for ((acc, t), w) in self.overlap.iter_mut().zip(&self.time).zip(&self.window) {
+            *acc += t * w * scale;
+        }
+
+        let skip = self.latency.min(HOP_SIZE);
+        self.latency -= skip;
+        output.extend_from_slice(&self.overlap[skip..HOP_SIZE]);
+        self.output_count += (HOP_SIZE - skip) as u64;
+
+        self.overlap.copy_within(HOP_SIZE.., 0);
+        self.overlap[FRAME_SIZE - HOP_SIZE..].fill(0.0);
+    }
+
+    fn update_gains(&mut self) {
+        let initializing = self.noise_frames < NOISE_INIT_FRAMES;
+        self.noise_frames += 1;
+
+        for ((bin, noise), gain) in self.spectrum.iter().zip(&mut self.noise).zip(&mut self.gains) {
+            let magnitude = bin.norm();
+
+            if initializing {
+                // Running average over the first frames
+                *noise += (magnitude - *noise) / self.noise_frames as f32;
+            } else if magnitude < *noise {
+                *noise += (magnitude - *noise) * NOISE_ATTACK;
+            } else {
+                *noise += (magnitude - *noise) * NOISE_RELEASE;
+            }
+
+            let target = if magnitude > 0.0 {
+                (1.0 - self.over_subtraction * *noise / magnitude).max(self.gain_floor)
+            } else {
+                self.gain_floor
+            };
+            *gain = GAIN_SMOOTHING * *gain + (1.0 - GAIN_SMOOTHING) * target;
+        }
+    }
+}
+
+/// Collects per-block energies to estimate the signal-to-noise ratio.
+#[derive(Default)]
+struct SnrMeter {
+    block: Vec<f32>,
+    energies: Vec<f32>,
+}
+
+impl SnrMeter {
+    fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.block.push(sample);
+            if self.block.len() == FRAME_SIZE {
+                self.energies.push(vad::frame_energy_db(&self.block));
+                self.block.clear();
+            }
+        }
+    }
+
+    fn snr_db(&self) -> Option<f32> {
+        vad::estimate_snr_db(&self.energies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f32 = 16000.0;
+
+    // Deterministic white noise (xorshift32)
+    fn noise(samples: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491u32;
+        (0..samples)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                amplitude * (state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            })
+            .collect()
+    }
+
+    fn denoise(strength: f32, input: &[f32]) -> Vec<f32> {
+        let mut denoiser = Denoiser::new(strength);
+        let mut output = Vec::new();
+        for chunk in input.chunks(700) {
+            denoiser.process(chunk, &mut output);
+        }
+        denoiser.flush(&mut output);
+        output
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Amplitude of the `freq` component (single-bin DFT).
+    fn amplitude_at(samples: &[f32], freq: f32) -> f32 {
+        let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+            let w = 2.0 * PI * freq * i as f32 / RATE;
+            (re + x * w.cos(), im - x * w.sin())
+        });
+        2.0 * (re * re + im * im).sqrt() / samples.len() as f32
+    }
+
+    #[test]
+    fn attenuates_stationary_noise() {
+        let input = noise(3 * RATE as usize, 0.05);
+        let output = denoise(1.0, &input);
+        assert_eq!(output.len(), input.len());
+
+        // Judge after the initial noise estimate has settled
+        let settled = RATE as usize / 2..input.len();
+        let reduction_db = 20.0 * (rms(&output[settled.clone()]) / rms(&input[settled])).log10();
+        assert!(reduction_db < -15.0, "noise only reduced by {:.1} dB", reduction_db);
+    }
+
+    #[test]
+    fn speech_band_tone_passes() {
+        // Background noise, then 200 ms bursts of 1 kHz (syllable-length) over it
+        let mut input = noise(2 * RATE as usize, 0.01);
+        let burst = RATE as usize / 5;
+        for start in (RATE as usize / 2..input.len()).step_by(2 * burst) {
+            for i in start..(start + burst).min(input.len()) {
+                input[i] += 0.2 * (2.0 * PI * 1000.0 * i as f32 / RATE).sin();
+            }
+        }
+        let output = denoise(1.0, &input);
+
+        let burst = 21200..23600; // the third burst, away from its edges
+        let before = amplitude_at(&input[burst.clone()], 1000.0);
+        let after = amplitude_at(&output[burst], 1000.0);
+        assert!(after / before > 0.85, "tone kept only {:.0}%", 100.0 * after / before);
+    }
+
+    #[test]
+    fn zero_strength_is_a_no_op() {
+        let mut input = noise(RATE as usize, 0.05);
+        for (i, sample) in input.iter_mut().enumerate() {
+            *sample += 0.2 * (2.0 * PI * 440.0 * i as f32 / RATE).sin();
+        }
+        let output = denoise(0.0, &input);
+
+        assert_eq!(output.len(), input.len());
+        let max_error = input.iter().zip(&output).map(|(a, b)| (a - b).abs()).fold(0.0, f32::max);
+        assert!(max_error < 1e-4, "output differs by up to {}", max_error);
+    }
+}
@@ -0,0 +1,168 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub channels: u16, // In the device's default config, 0 if unknown
+}
+
+/// List the input devices exposed by the default audio host.
+/// cpal only exposes device names, so the name doubles as the device ID.
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_input_device()
+        .and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?;
+
+    let mut result = Vec::new();
+    for device in devices {
+        let name = match device.name() {
+            Ok(n) => n,
+            Err(e) => {
+                log::warn!("Skipping input device without a name: {}", e);
+                continue;
+            }
+        };
+        // Some hosts report the same device more than once
+        if result.iter().any(|d: &InputDeviceInfo| d.name == name) {
+            continue;
+        }
+        let is_default = default_name.as_deref() == Some(name.as_str());
+        let channels = device.default_input_config().map(|c| c.channels()).unwrap_or(0);
+        result.push(InputDeviceInfo { name, is_default, channels });
+    }
+
+    Ok(result)
+}
+
+/// Open the requested input device, falling back to the system default when it
+/// is not configured or no longer present. The second element of the returned
+/// tuple describes the fallback, if one happened.
+pub fn open_input_device(
+    host: &cpal::Host,
+    preferred: Option<&str>,
+) -> Result<(cpal::Device, Option<String>), String> {
+    let preferred = preferred.map(str::trim).filter(|name| !name.is_empty());
+
+    if let Some(name) = preferred {
+        match host.input_devices() {
+            Ok(mut devices) => {
+                if let Some(device) = devices.find(|d| d.name().map(|n| n == name).unwrap_or(false)) {
+                    return Ok((device, None));
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to enumerate input devices: {}", e);
+            }
+        }
+    }
+
+    let device = host
+        .default_input_device()
+        .ok_or("No input device available")?;
+
+    let warning = preferred.map(|name| {
+        let default_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        log::warn!("Input device '{}' not found, falling back to default '{}'", name, default_name);
+        format!(
+            "Microphone '{}' not found, used default device '{}' instead",
+            name, default_name
+        )
+    });
+
+    Ok((device, warning))
+}
+
+/// True for PulseAudio/PipeWire monitor sources, which record what a sink plays.
+pub fn is_monitor_device(name: &str) -> bool {
+    name.to_lowercase().contains("monitor")
+}
+
+/// List the devices system audio can be captured from. On Windows every output
+/// device can be opened for loopback through WASAPI; elsewhere these are input
+/// devices that carry playback, i.e. monitor sources or a loopback driver.
+pub fn list_loopback_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    let host = cpal::default_host();
+    let mut result: Vec<InputDeviceInfo> = Vec::new();
+
+    if cfg!(target_os = "windows") {
+        let default_name = host.default_output_device().and_then(|d| d.name().ok());
+        let devices = host
+            .output_devices()
+            .map_err(|e| format!("Failed to enumerate output devices: {}", e))?;
+        for device in devices {
+            let Ok(name) = device.name() else { continue };
+            if result.iter().any(|d| d.name == name) {
+                continue;
+            }
+            let is_default = default_name.as_deref() == Some(name.as_str());
+            let channels = device.default_output_config().map(|c| c.channels()).unwrap_or(0);
+            result.push(InputDeviceInfo { name, is_default, channels });
+        }
+        return Ok(result);
+    }
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?;
+    for device in devices {
+        let Ok(name) = device.name() else { continue };
+        if !is_monitor_device(&name) || result.iter().any(|d| d.name == name) {
+            continue;
+        }
+        let channels = device.default_input_config().map(|c| c.channels()).unwrap_or(0);
+        // There is no default monitor, the first one usually belongs to the default sink
+        let is_default = result.is_empty();
+        result.push(InputDeviceInfo { name, is_default, channels });
+    }
+    Ok(result)
+}
+
+/// Open the requested loopback device, or the default one (see
+/// `list_loopback_devices`) when it is not configured or no longer present.
+/// The second element of the returned tuple describes the fallback, if one happened.
+pub fn open_loopback_device(
+    host: &cpal::Host,
+    preferred: Option<&str>,
+) -> Result<(cpal::Device, Option<String>), String> {
+    let preferred = preferred.map(str::trim).filter(|name| !name.is_empty());
+
+    let mut devices: Vec<cpal::Device> = if cfg!(target_os = "windows") {
+        host.output_devices()
+            .map_err(|e| format!("Failed to enumerate output devices: {}", e))?
+            .collect()
+    } else {
+        host.input_devices()
+            .map_err(|e| format!("Failed to enumerate input devices: {}", e))?
+            .collect()
+    };
+    let name_of = |d: &cpal::Device| d.name().unwrap_or_default();
+
+    if let Some(index) = preferred.and_then(|name| devices.iter().position(|d| name_of(d) == name)) {
+        return Ok((devices.swap_remove(index), None));
+    }
+
+    let device = if cfg!(target_os = "windows") {
+        host.default_output_device()
+    } else {
+        devices.into_iter().find(|d| is_monitor_device(&name_of(d)))
+    };
+    let device = device.ok_or(
+        "No system audio device found. On Linux enable a PulseAudio/PipeWire monitor source; \
+         on macOS install a loopback driver (e.g. BlackHole) and select it",
+    )?;
+
+    let warning = preferred.map(|name| {
+        let default_name = name_of(&device);
+        log::warn!("Loopback device '{}' not found, falling back to '{}'", name, default_name);
+        format!("System audio device '{}' not found, used '{}' instead", name, default_name)
+    });
+
+    Ok((device, warning))
+}
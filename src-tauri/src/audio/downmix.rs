@@ -0,0 +1,211 @@
+// Time constant of the per-channel level used to follow the loudest channel
+const LOUDEST_SMOOTHING_SECS: f32 = 0.3;
+// Another channel must be this much louder (power ratio, about 3 dB) to take over
+const LOUDEST_SWITCH_RATIO: f32 = 2.0;
+
+/// How multi-channel input is reduced to mono.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ChannelMix {
+    /// Average all channels.
+    #[default]
+    Average,
+    /// Use a single channel (0-based), e.g. the one input with a mic plugged in.
+    Channel(usize),
+    /// Follow whichever channel currently carries the most energy.
+    Loudest,
+}
+
+impl ChannelMix {
+    /// Parse the config form: "average", "loudest" or "channel-N" (1-based).
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "loudest" => ChannelMix::Loudest,
+            _ => name
+                .strip_prefix("channel-")
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|&n| n >= 1)
+                .map_or(ChannelMix::Average, |n| ChannelMix::Channel(n - 1)),
+        }
+    }
+}
+
+/// Converts interleaved frames to mono according to a `ChannelMix`.
+pub struct Downmixer {
+    mix: ChannelMix,
+    channels: usize,
+    sample_rate: u32,
+    // Smoothed power per channel, only tracked for `Loudest`
+    levels: Vec<f32>,
+    current: usize,
+}
+
+impl Downmixer {
+    pub fn new(mix: ChannelMix, channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let mix = match mix {
+            ChannelMix::Channel(n) if n >= channels => {
+                log::warn!("Input channel {} not available ({} channels), averaging instead", n + 1, channels);
+                println!(">>> WARN: Input channel {} not available ({} channels), averaging instead", n + 1, channels);
+                ChannelMix::Average
+            }
+            mix => mix,
+        };
+        if channels > 1 {
+            println!(">>> Downmixing {} channels: {:?}", channels, mix);
+        }
+
+        Self {
+            mix,
+            channels,
+            sample_rate,
+            levels: vec![0.0; channels],
+            current: 0,
+        }
+    }
+
+    /// Append one mono sample per whole frame of `interleaved` to `output`.
+    pub fn process(&mut self, interleaved: &[f32], output: &mut Vec<f32>) {
+        if self.channels == 1 {
+            output.extend_from_slice(interleaved);
+            return;
+        }
+
+        let frames = interleaved.chunks_exact(self.channels);
+        match self.mix {
+            ChannelMix::Average => {
+                output.extend(frames.map(|frame| frame.iter().sum::<f32>() / self.channels as f32))
+            }
+            ChannelMix::Channel(n) => output.extend(frames.map(|frame| frame[n])),
+            ChannelMix::Loudest => self.process_loudest(interleaved, output),
+        }
+    }
+
+    fn process_loudest(&mut self, interleaved: &[f32], output: &mut Vec<f32>) {
+        let frame_count = interleaved.len() / self.channels;
+        if frame_count == 0 {
+            return;
+        }
+
+        // Update the smoothed power of each channel with this chunk
+        let alpha = 1.0 - (-(frame_count as f32) / (LOUDEST_SMOOTHING_SECS * self.sample_rate as f32)).exp();
+        for (channel, level) in self.levels.iter_mut().enumerate() {
+            let power = interleaved
+                .iter()
+                .skip(channel)
+                .step_by(self.channels)
+                .map(|s| s * s)
+                .sum::<f32>()
+                / frame_count as f32;
+            *level += alpha * (power - *level);
+        }
+
+        let previous = self.current;
+        let (loudest, level) = self
+            .levels
+            .iter()
+            .copied()
+            .enumerate()
+            .fold((previous, 0.0), |best, (channel, level)| if level > best.1 { (channel, level) } else { best });
+        if loudest != previous && level > self.levels[previous] * LOUDEST_SWITCH_RATIO {
+            println!(">>> Following input channel {} (loudest)", loudest + 1);
+            self.current = loudest;
+        }
+
+        let frames = interleaved.chunks_exact(self.channels);
+        if self.current == previous {
+            output.extend(frames.map(|frame| frame[previous]));
+        } else {
+            // Crossfade across the chunk so the switch doesn't click
+            let current = self.current;
+            output.extend(frames.enumerate().map(|(i, frame)| {
+                let t = (i + 1) as f32 / frame_count as f32;
+                frame[previous] * (1.0 - t) + frame[current] * t
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48000;
+    // 10 ms of stereo audio
+    const CHUNK_FRAMES: usize = 480;
+
+    fn stereo_chunk(left: f32, right: f32) -> Vec<f32> {
+        [left, right].repeat(CHUNK_FRAMES)
+    }
+
+    fn downmix(downmixer: &mut Downmixer, interleaved: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        downmixer.process(interleaved, &mut output);
+        output
+    }
+
+    #[test]
+    fn parses_config_names() {
+        assert_eq!(ChannelMix::from_name("average"), ChannelMix::Average);
+        assert_eq!(ChannelMix::from_name("loudest"), ChannelMix::Loudest);
+        assert_eq!(ChannelMix::from_name("channel-1"), ChannelMix::Channel(0));
+        assert_eq!(ChannelMix::from_name("channel-3"), ChannelMix::Channel(2));
+        // Channels are 1-based, anything unparseable averages
+        assert_eq!(ChannelMix::from_name("channel-0"), ChannelMix::Average);
+        assert_eq!(ChannelMix::from_name("channel-"), ChannelMix::Average);
+        assert_eq!(ChannelMix::from_name("channel-two"), ChannelMix::Average);
+        assert_eq!(ChannelMix::from_name(""), ChannelMix::Average);
+    }
+
+    #[test]
+    fn averages_channels() {
+        let mut downmixer = Downmixer::new(ChannelMix::Average, 3, RATE);
+        assert_eq!(downmix(&mut downmixer, &[0.3, 0.6, 0.0, -0.3, -0.3, 0.6]), vec![0.3, 0.0]);
+    }
+
+    #[test]
+    fn mono_passes_through() {
+        let mut downmixer = Downmixer::new(ChannelMix::Channel(0), 1, RATE);
+        assert_eq!(downmix(&mut downmixer, &[0.1, 0.2, 0.3]), vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn picks_a_single_channel() {
+        let mut downmixer = Downmixer::new(ChannelMix::Channel(1), 2, RATE);
+        assert_eq!(downmix(&mut downmixer, &[0.1, 0.2, 0.3, 0.4]), vec![0.2, 0.4]);
+    }
+
+    #[test]
+    fn out_of_range_channel_averages() {
+        let mut downmixer = Downmixer::new(ChannelMix::Channel(2), 2, RATE);
+        assert_eq!(downmix(&mut downmixer, &[0.2, 0.4, 0.0, -0.2]), vec![0.3, -0.1]);
+    }
+
+    #[test]
+    fn follows_the_loudest_channel() {
+        let mut downmixer = Downmixer::new(ChannelMix::Loudest, 2, RATE);
+
+        // The right channel carries the signal from the start
+        downmix(&mut downmixer, &stereo_chunk(0.01, 0.5));
+        assert_eq!(downmix(&mut downmixer, &stereo_chunk(0.01, 0.5)), vec![0.5; CHUNK_FRAMES]);
+
+        // Then the left channel takes over, once its level has built up
+        let mut output = Vec::new();
+        for _ in 0..100 {
+            output = downmix(&mut downmixer, &stereo_chunk(0.5, 0.01));
+        }
+        assert_eq!(output, vec![0.5; CHUNK_FRAMES]);
+    }
+
+    #[test]
+    fn loudest_needs_a_clear_margin_to_switch() {
+        let mut downmixer = Downmixer::new(ChannelMix::Loudest, 2, RATE);
+        downmix(&mut downmixer, &stereo_chunk(0.4, 0.0));
+
+        // The right channel is only slightly louder (about 1 dB), stay on the left
+        let mut output = Vec::new();
+        for _ in 0..100 {
+            output = downmix(&mut downmixer, &stereo_chunk(0.4, 0.45));
+        }
+        assert_eq!(output, vec![0.4; CHUNK_FRAMES]);
+    }
+}
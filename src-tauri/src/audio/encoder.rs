@@ -0,0 +1,500 @@
+use audiopus::{coder::Encoder, Application, Channels, SampleRate};
+use ogg::writing::PacketWriteEndInfo;
+use std::io::Cursor;
+
+use super::vad;
+use super::TARGET_SAMPLE_RATE;
+use crate::config::EncodingConfig;
+
+// Frame durations Opus can encode, in ms
+const OPUS_FRAME_DURATIONS: [u32; 4] = [10, 20, 40, 60];
+const DEFAULT_FRAME_MS: u32 = 60; // Recommended for voice
+const MAX_OPUS_PACKET_SIZE: usize = 4000;
+// Opus granule positions and pre-skip always count samples at 48 kHz
+const OPUS_GRANULE_RATE: u32 = 48000;
+
+/// Container/codec of the audio sent for transcription.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UploadFormat {
+    /// Opus in an OGG container, about 2 KB per second at 16 kbps.
+    #[default]
+    Ogg,
+    /// 16-bit PCM WAV, 32 KB per second but lossless.
+    Wav,
+}
+
+impl UploadFormat {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "wav" => UploadFormat::Wav,
+            _ => UploadFormat::Ogg,
+        }
+    }
+
+    /// Tell which format encoded audio is in from its header.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(b"RIFF") {
+            UploadFormat::Wav
+        } else {
+            UploadFormat::Ogg
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            UploadFormat::Ogg => "audio.ogg",
+            UploadFormat::Wav => "audio.wav",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            UploadFormat::Ogg => "audio/ogg",
+            UploadFormat::Wav => "audio/wav",
+        }
+    }
+}
+
+/// How recordings are encoded for upload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncoderSettings {
+    pub format: UploadFormat,
+    pub bitrate_kbps: u32,
+    pub complexity: u8, // 0 (fastest) - 10 (best quality)
+    pub vbr: bool,
+    pub fec: bool, // In-band forward error correction
+    pub frame_ms: u32,
+}
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        Self {
+            format: UploadFormat::Ogg,
+            bitrate_kbps: 16, // Good for speech
+            complexity: 10,
+            vbr: true,
+            fec: false,
+            frame_ms: DEFAULT_FRAME_MS,
+        }
+    }
+}
+
+impl From<&EncodingConfig> for EncoderSettings {
+    fn from(config: &EncodingConfig) -> Self {
+        Self {
+            format: UploadFormat::from_name(&config.upload_format),
+            bitrate_kbps: config.bitrate_kbps,
+            complexity: config.complexity,
+            vbr: config.vbr,
+            fec: config.fec,
+            frame_ms: config.frame_ms,
+        }
+    }
+}
+
+/// Encoder that accepts 16 kHz mono audio in arbitrary chunks.
+///
+/// Frames are encoded as soon as they fill up and only the packets (Opus,
+/// or raw PCM for WAV uploads) are kept, together with each frame's level
+/// so silence can still be trimmed when the stream is finished.
+pub struct StreamEncoder {
+    // None when uploading WAV
+    encoder: Option<Encoder>,
+    // Encoder delay in 16 kHz samples, announced as the OGG pre-skip
+    lookahead: usize,
+    frame_size: usize,
+    frame_ms: u32,
+    frame: Vec<f32>,
+    pcm: Vec<i16>,
+    packets: Vec<Vec<u8>>,
+    frame_energies: Vec<f32>,
+    sample_count: u64,
+    encoded_bytes: usize,
+    scratch: Vec<u8>,
+}
+
+impl StreamEncoder {
+    pub fn new(settings: &EncoderSettings) -> Result<Self, String> {
+        let frame_ms = if OPUS_FRAME_DURATIONS.contains(&settings.frame_ms) {
+            settings.frame_ms
+        } else {
+            log::warn!("Unsupported Opus frame duration {} ms, using {} ms", settings.frame_ms, DEFAULT_FRAME_MS);
+            DEFAULT_FRAME_MS
+        };
+        let frame_size = (TARGET_SAMPLE_RATE * frame_ms / 1000) as usize;
+
+        let encoder = match settings.format {
+            UploadFormat::Ogg => Some(create_opus_encoder(settings)?),
+            UploadFormat::Wav => None,
+        };
+        let lookahead = match encoder {
+            Some(ref encoder) => encoder
+                .lookahead()
+                .map_err(|e| format!("Failed to get encoder lookahead: {:?}", e))? as usize,
+            None => 0,
+        };
+        println!(
+            ">>> Encoder: {:?}, {} kbps, complexity {}, VBR {}, FEC {}, {} ms frames",
+            settings.format, settings.bitrate_kbps, settings.complexity, settings.vbr, settings.fec, frame_ms
+        );
+
+        Ok(Self {
+            encoder,
+            lookahead,
+            frame_size,
+            frame_ms,
+            frame: Vec::with_capacity(frame_size),
+            pcm: Vec::with_capacity(frame_size),
+            packets: Vec::new(),
+            frame_energies: Vec::new(),
+            sample_count: 0,
+            encoded_bytes: 0,
+            scratch: vec![0u8; MAX_OPUS_PACKET_SIZE],
+        })
+    }
+
+    /// Number of 16 kHz samples pushed so far.
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+    /// Size of the Opus packets produced so far.
+    pub fn encoded_bytes(&self) -> usize {
+        self.encoded_bytes
+    }
+
+    pub fn push(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.sample_count += samples.len() as u64;
+        self.append(samples)
+    }
+
+    fn append(&mut self, samples: &[f32]) -> Result<(), String> {
+        let mut remaining = samples;
+        while !remaining.is_empty() {
+            let take = (self.frame_size - self.frame.len()).min(remaining.len());
+            self.frame.extend_from_slice(&remaining[..take]);
+            remaining = &remaining[take..];
+            if self.frame.len() == self.frame_size {
+                self.encode_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_frame(&mut self) -> Result<(), String> {
+        // Pad last frame if needed
+        self.frame.resize(self.frame_size, 0.0);
+        self.frame_energies.push(vad::frame_energy_db(&self.frame));
+
+        // Convert f32 samples to i16
+        self.pcm.clear();
+        self.pcm.extend(
+            self.frame
+                .iter()
+                .map(|s| (*s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16),
+        );
+
+        let packet = match self.encoder {
+            Some(ref encoder) => {
+                let encoded_len = encoder.encode(&self.pcm, &mut self.scratch)
+                    .map_err(|e| format!("Failed to encode Opus frame: {:?}", e))?;
+                self.scratch[..encoded_len].to_vec()
+            }
+            None => self.pcm.iter().flat_map(|s| s.to_le_bytes()).collect(),
+        };
+
+        self.encoded_bytes += packet.len();
+        self.packets.push(packet);
+        self.frame.clear();
+        Ok(())
+    }
+
+    /// Encode the final partial frame and wrap the packets in an OGG stream (or a WAV file).
+    /// With `trim_silence`, packets before and after the detected speech are
+    /// dropped, and a recording without speech is rejected.
+    pub fn finish(mut self, trim_silence: bool) -> Result<Vec<u8>, String> {
+        // Feed the encoder delay worth of silence so the last real samples come out
+        if self.lookahead > 0 {
+            self.append(&vec![0.0; self.lookahead])?;
+        }
+        if !self.frame.is_empty() {
+            self.encode_frame()?;
+        }
+
+        println!(
+            ">>> StreamEncoder: encoded {} frames, {} bytes of audio data",
+            self.packets.len(),
+            self.encoded_bytes
+        );
+
+        let (start, end) = if trim_silence {
+            match vad::find_speech(&self.frame_energies, self.frame_ms) {
+                Some((start, end)) => {
+                    let pad = vad::padding_frames(self.frame_ms);
+                    (start.saturating_sub(pad), (end + pad).min(self.packets.len()))
+                }
+                None => {
+                    log::warn!("No speech detected in recording");
+                    println!(">>> WARNING: No speech detected in recording");
+                    return Err(vad::NO_SPEECH_DETECTED.to_string());
+                }
+            }
+        } else {
+            (0, self.packets.len())
+        };
+
+        if start > 0 || end < self.packets.len() {
+            println!(">>> VAD trimmed {} -> {} frames", self.packets.len(), end - start);
+        }
+
+        // Input samples covered by the kept packets; their output lags the input by the lookahead
+        let first = start * self.frame_size;
+        let last = (end * self.frame_size)
+            .saturating_sub(self.lookahead)
+            .min(self.sample_count as usize);
+        let sample_count = last.saturating_sub(first);
+
+        match self.encoder {
+            Some(_) => write_ogg(&self.packets[start..end], self.frame_size, self.lookahead, sample_count),
+            None => write_wav(&self.packets[start..end], sample_count),
+        }
+    }
+}
+
+fn create_opus_encoder(settings: &EncoderSettings) -> Result<Encoder, String> {
+    let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Failed to create Opus encoder: {:?}", e))?;
+
+    encoder.set_bitrate(audiopus::Bitrate::BitsPerSecond(settings.bitrate_kbps.clamp(6, 510) as i32 * 1000))
+        .map_err(|e| format!("Failed to set bitrate: {:?}", e))?;
+    encoder.set_complexity(settings.complexity.min(10))
+        .map_err(|e| format!("Failed to set complexity: {:?}", e))?;
+    encoder.set_vbr(settings.vbr)
+        .map_err(|e| format!("Failed to set VBR: {:?}", e))?;
+    encoder.set_inband_fec(settings.fec)
+        .map_err(|e| format!("Failed to set FEC: {:?}", e))?;
+    if settings.fec {
+        // The encoder only spends bits on FEC when it expects some packet loss
+        encoder.set_packet_loss_perc(10)
+            .map_err(|e| format!("Failed to set expected packet loss: {:?}", e))?;
+    }
+
+    Ok(encoder)
+}
+
+/// Write the PCM packets as a WAV file, dropping the padding after `sample_count` samples.
+fn write_wav(packets: &[Vec<u8>], sample_count: usize) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec)
+        .map_err(|e| format!("Failed to create WAV stream: {}", e))?;
+    for sample in packets.iter().flat_map(|p| p.chunks_exact(2)).take(sample_count) {
+        writer
+            .write_sample(i16::from_le_bytes([sample[0], sample[1]]))
+            .map_err(|e| format!("Failed to write WAV stream: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finish WAV stream: {}", e))?;
+
+    let result = cursor.into_inner();
+    println!(">>> WAV file size: {} bytes", result.len());
+    Ok(result)
+}
+
+/// Wrap Opus packets in an OGG stream (RFC 7845). `pre_skip` and
+/// `sample_count` are in 16 kHz samples; the final granule position is set so
+/// decoders drop the padding of the last frame.
+fn write_ogg(packets: &[Vec<u8>], frame_size: usize, pre_skip: usize, sample_count: usize) -> Result<Vec<u8>, String> {
+    let scale = (OPUS_GRANULE_RATE / TARGET_SAMPLE_RATE) as u64;
+    let pre_skip = pre_skip as u64 * scale;
+
+    // Create OGG container
+    let mut cursor = Cursor::new(Vec::new());
+    let serial = rand_serial();
+    let mut packet_writer = ogg::writing::PacketWriter::new(&mut cursor);
+
+    // Write Opus header (OpusHead)
+    let opus_head = create_opus_head(pre_skip.min(u16::MAX as u64) as u16);
+    packet_writer.write_packet(opus_head, serial, PacketWriteEndInfo::EndPage, 0)
+        .map_err(|e| format!("Failed to write OpusHead: {}", e))?;
+
+    // Write Opus comment header (OpusTags)
+    let opus_tags = create_opus_tags();
+    packet_writer.write_packet(opus_tags, serial, PacketWriteEndInfo::EndPage, 0)
+        .map_err(|e| format!("Failed to write OpusTags: {}", e))?;
+
+    let mut granule_pos: u64 = 0;
+    for (idx, packet) in packets.iter().enumerate() {
+        // Samples decoded so far, including the pre-skip
+        granule_pos += frame_size as u64 * scale;
+
+        let end_info = if idx == packets.len() - 1 {
+            // End trimming: the last granule marks the true end of the audio
+            granule_pos = pre_skip + sample_count as u64 * scale;
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+
+        packet_writer.write_packet(packet.clone(), serial, end_info, granule_pos)
+            .map_err(|e| format!("Failed to write Opus packet: {}", e))?;
+    }
+
+    let result = cursor.into_inner();
+    log::info!("Wrote {} Opus packets to {} bytes Opus/OGG", packets.len(), result.len());
+    println!(">>> OGG file size: {} bytes", result.len());
+
+    Ok(result)
+}
+
+/// Duration of encoded upload audio (OGG/Opus or WAV), read from its headers.
+pub fn encoded_duration_secs(data: &[u8]) -> Option<f64> {
+    match UploadFormat::detect(data) {
+        UploadFormat::Ogg => ogg_duration_secs(data),
+        UploadFormat::Wav => {
+            let reader = hound::WavReader::new(Cursor::new(data)).ok()?;
+            Some(reader.duration() as f64 / reader.spec().sample_rate as f64)
+        }
+    }
+}
+
+/// Duration of an Ogg Opus stream: the granule position of its last page minus
+/// the pre-skip announced in its OpusHead packet.
+pub fn ogg_duration_secs(data: &[u8]) -> Option<f64> {
+    let mut reader = ogg::reading::PacketReader::new(Cursor::new(data));
+    let head = reader.read_packet().ok()??;
+    if head.data.len() < 19 || !head.data.starts_with(b"OpusHead") {
+        return None;
+    }
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+
+    // Packets report the granule position of the page they end on
+    let mut granule = head.absgp_page();
+    while let Some(packet) = reader.read_packet().ok()? {
+        granule = packet.absgp_page();
+    }
+    Some(granule.saturating_sub(pre_skip) as f64 / OPUS_GRANULE_RATE as f64)
+}
+
+fn rand_serial() -> u32 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u32)
+        .unwrap_or(12345678)
+}
+
+fn create_opus_head(pre_skip: u16) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");  // Magic signature
+    head.push(1);                          // Version
+    head.push(1);                          // Channel count (mono)
+    head.extend_from_slice(&pre_skip.to_le_bytes());  // Pre-skip (encoder delay, 48 kHz samples)
+    head.extend_from_slice(&TARGET_SAMPLE_RATE.to_le_bytes()); // Input sample rate (informational, Opus decodes at 48 kHz)
+    head.extend_from_slice(&0i16.to_le_bytes());  // Output gain
+    head.push(0);                          // Channel mapping family
+    head
+}
+
+fn create_opus_tags() -> Vec<u8> {
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");  // Magic signature
+    let vendor = b"FluxVoice";
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(format: UploadFormat) -> EncoderSettings {
+        EncoderSettings { format, ..Default::default() }
+    }
+
+    fn tone(samples: usize, amplitude: f32) -> Vec<f32> {
+        (0..samples)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / TARGET_SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn encode(format: UploadFormat, samples: &[f32], trim_silence: bool) -> Result<Vec<u8>, String> {
+        let mut encoder = StreamEncoder::new(&settings(format))?;
+        // Feed uneven chunks, as the capture thread does
+        for chunk in samples.chunks(317) {
+            encoder.push(chunk)?;
+        }
+        encoder.finish(trim_silence)
+    }
+
+    #[test]
+    fn silent_recording_is_rejected_when_trimming() {
+        let silence = vec![0.0; TARGET_SAMPLE_RATE as usize];
+        assert_eq!(encode(UploadFormat::Wav, &silence, true), Err(vad::NO_SPEECH_DETECTED.to_string()));
+        assert!(encode(UploadFormat::Wav, &silence, false).is_ok());
+    }
+
+    #[test]
+    fn trims_silence_around_speech() {
+        let second = TARGET_SAMPLE_RATE as usize;
+        let mut samples = vec![0.0; second];
+        samples.extend(tone(second, 0.3));
+        samples.extend(vec![0.0; second]);
+
+        let untrimmed = encoded_duration_secs(&encode(UploadFormat::Wav, &samples, false).unwrap()).unwrap();
+        assert_eq!(untrimmed, 3.0);
+
+        // One second of speech plus up to 300 ms of padding (rounded to frames) on each side
+        let trimmed = encoded_duration_secs(&encode(UploadFormat::Wav, &samples, true).unwrap()).unwrap();
+        assert!((1.5..=1.8).contains(&trimmed), "trimmed to {} s", trimmed);
+    }
+
+    fn opus_pre_skip(ogg: &[u8]) -> u16 {
+        let head = ogg::reading::PacketReader::new(Cursor::new(ogg)).read_packet().unwrap().expect("no OpusHead");
+        u16::from_le_bytes([head.data[10], head.data[11]])
+    }
+
+    #[test]
+    fn opus_round_trip_keeps_exact_length() {
+        // Not a multiple of any frame size
+        let sample_count = 20_017;
+        for frame_ms in OPUS_FRAME_DURATIONS {
+            let mut encoder = StreamEncoder::new(&EncoderSettings { frame_ms, ..Default::default() }).unwrap();
+            for chunk in tone(sample_count, 0.3).chunks(317) {
+                encoder.push(chunk).unwrap();
+            }
+            let ogg = encoder.finish(false).unwrap();
+            assert!(opus_pre_skip(&ogg) > 0, "no pre-skip with {} ms frames", frame_ms);
+            let duration = encoded_duration_secs(&ogg).unwrap();
+            assert!((duration - sample_count as f64 / TARGET_SAMPLE_RATE as f64).abs() < 1e-9, "{} s", duration);
+
+            // Decoded at 48 kHz, with the pre-skip and the padding after the end granule dropped
+            let decoded = crate::audio::decode::decode_bytes(ogg, None).unwrap();
+            let scale = (OPUS_GRANULE_RATE / TARGET_SAMPLE_RATE) as usize;
+            assert_eq!(decoded.sample_rate, OPUS_GRANULE_RATE);
+            assert_eq!(decoded.samples.len(), sample_count * scale, "wrong length with {} ms frames", frame_ms);
+        }
+    }
+
+    #[test]
+    fn ogg_duration_ignores_page_markers_in_packets() {
+        // Payload bytes that look like a page header and an OpusHead with absurd values
+        let mut packet = b"OggS".to_vec();
+        packet.extend([0xff; 24]);
+        packet.extend_from_slice(b"OpusHead");
+        packet.extend([0xff; 24]);
+
+        // Two packets covering one second at 16 kHz, after a 104-sample (312 at 48 kHz) pre-skip
+        let ogg = write_ogg(&[packet.clone(), packet], 8000, 104, 16000).unwrap();
+        assert_eq!(opus_pre_skip(&ogg), 312);
+        assert_eq!(encoded_duration_secs(&ogg), Some(1.0));
+    }
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::meter::AudioLevels;
+
+/// Notifications raised on the capture thread. `lib.rs` installs a handler
+/// that forwards them to the frontend as Tauri events.
+#[derive(Debug, Clone)]
+pub enum RecorderEvent {
+    /// The recording hit a limit and was stopped by the recorder itself.
+    LimitReached(RecordingLimit),
+    /// Periodic metering update while recording.
+    Level(AudioLevels),
+    /// The input stream failed, e.g. because the device was unplugged.
+    StreamError(RecordingError),
+    /// The recording was paused or resumed.
+    Paused(PauseState),
+    /// Hands-free: trailing silence is counting down to an automatic stop.
+    SilenceCountdown(SilenceCountdown),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingLimit {
+    pub reason: String, // "duration", "size" or, in hands-free mode, "silence"
+    pub duration_secs: f32,
+    pub encoded_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingError {
+    pub message: String,
+    /// True if capture continues on a fallback device; otherwise the recording
+    /// was stopped and the audio captured so far is handed out at the next stop.
+    pub recovered: bool,
+    pub device: Option<String>, // Device capture continues on, when recovered
+    pub duration_secs: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PauseState {
+    pub paused: bool,
+    pub duration_secs: f32, // Audio recorded so far, not counting pauses
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SilenceCountdown {
+    pub remaining_ms: Option<u32>, // None when speech resumed and the countdown was cancelled
+    pub timeout_ms: u32,
+}
+
+pub type EventHandler = Arc<dyn Fn(RecorderEvent) + Send + Sync>;
@@ -0,0 +1,193 @@
+use super::vad;
+use super::TARGET_SAMPLE_RATE;
+
+// Gain is re-evaluated every 10ms
+const BLOCK_SIZE: usize = (TARGET_SAMPLE_RATE / 100) as usize;
+// Level speech is normalized to
+const TARGET_LEVEL_DB: f32 = -20.0;
+// Blocks quieter than this are not speech; the gain drifts back to unity
+const GATE_DB: f32 = -55.0;
+const MAX_GAIN_DB: f32 = 24.0;
+const MIN_GAIN_DB: f32 = -12.0;
+// Per-block smoothing: fast to turn down, slow to turn up
+const ATTACK: f32 = 0.3;
+const RELEASE: f32 = 0.02;
+// Peaks above this are softly limited instead of clipped
+const LIMITER_THRESHOLD: f32 = 0.9;
+
+// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.99;
+// Warn when more than this fraction of samples clipped
+const CLIP_WARN_RATIO: f32 = 0.001;
+// Warn when the speech level stays below this
+const QUIET_WARN_DB: f32 = -42.0;
+
+/// Automatic gain control for 16 kHz mono audio, applied sample by sample
+/// without added latency. Speech is brought towards `TARGET_LEVEL_DB` and a
+/// soft limiter keeps the boosted signal from clipping.
+pub struct AutoGain {
+    gain: f32,
+    block_energy: f32,
+    block_len: usize,
+}
+
+impl AutoGain {
+    pub fn new() -> Self {
+        Self { gain: 1.0, block_energy: 0.0, block_len: 0 }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            self.block_energy += *sample * *sample;
+            self.block_len += 1;
+            if self.block_len == BLOCK_SIZE {
+                self.update_gain();
+            }
+            *sample = soft_limit(*sample * self.gain);
+        }
+    }
+
+    fn update_gain(&mut self) {
+        let rms = (self.block_energy / self.block_len as f32).sqrt();
+        self.block_energy = 0.0;
+        self.block_len = 0;
+
+        let level_db = if rms > 0.0 { 20.0 * rms.log10() } else { f32::NEG_INFINITY };
+        let target_db = if level_db >= GATE_DB {
+            (TARGET_LEVEL_DB - level_db).clamp(MIN_GAIN_DB, MAX_GAIN_DB)
+        } else {
+            // Don't boost background noise between words
+            0.0
+        };
+        let target = 10f32.powf(target_db / 20.0);
+        let rate = if target < self.gain { ATTACK } else { RELEASE };
+        self.gain += (target - self.gain) * rate;
+    }
+}
+
+impl Default for AutoGain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn soft_limit(x: f32) -> f32 {
+    let magnitude = x.abs();
+    if magnitude <= LIMITER_THRESHOLD {
+        return x;
+    }
+    let headroom = 1.0 - LIMITER_THRESHOLD;
+    let limited = LIMITER_THRESHOLD + headroom * ((magnitude - LIMITER_THRESHOLD) / headroom).tanh();
+    limited.copysign(x)
+}
+
+/// Watches the input for clipping and for speech that is too quiet to
+/// transcribe well, so the user can be told to adjust their mic gain.
+#[derive(Default)]
+pub struct LevelMonitor {
+    samples: u64,
+    clipped: u64,
+    block: Vec<f32>,
+    energies: Vec<f32>,
+}
+
+impl LevelMonitor {
+    /// Count clipped samples in raw device audio.
+    pub fn check_clipping(&mut self, samples: &[f32]) {
+        self.samples += samples.len() as u64;
+        self.clipped += samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count() as u64;
+    }
+
+    /// Track the level of 16 kHz mono audio before any gain is applied.
+    pub fn measure(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.block.push(sample);
+            if self.block.len() == BLOCK_SIZE {
+                self.energies.push(vad::frame_energy_db(&self.block));
+                self.block.clear();
+            }
+        }
+    }
+
+    /// Warnings for the user, if the input level looked wrong.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let clip_ratio = self.clipped as f32 / self.samples.max(1) as f32;
+        if clip_ratio > CLIP_WARN_RATIO {
+            log::warn!("Input clipping: {} of {} samples", self.clipped, self.samples);
+            warnings.push(format!(
+                "Microphone input is clipping ({:.1}% of samples) - lower the microphone gain",
+                clip_ratio * 100.0
+            ));
+        }
+
+        if let Some(level) = vad::speech_level_db(&self.energies) {
+            log::info!("Speech level: {:.1} dBFS", level);
+            if level < QUIET_WARN_DB {
+                warnings.push(format!(
+                    "Microphone input is very quiet ({:.0} dBFS) - raise the microphone gain or speak closer",
+                    level
+                ));
+            }
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: usize = TARGET_SAMPLE_RATE as usize;
+
+    fn tone(samples: usize, amplitude: f32) -> Vec<f32> {
+        (0..samples)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 300.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn level_db(samples: &[f32]) -> f32 {
+        vad::frame_energy_db(samples)
+    }
+
+    #[test]
+    fn raises_quiet_speech_towards_target() {
+        // About -43 dBFS, 23 dB below the target
+        let mut samples = tone(4 * RATE, 0.01);
+        let input_db = level_db(&samples);
+        let mut gain = AutoGain::new();
+        for chunk in samples.chunks_mut(480) {
+            gain.process(chunk);
+        }
+
+        let output_db = level_db(&samples[3 * RATE..]);
+        assert!(output_db > input_db + 15.0, "only raised from {:.1} to {:.1} dBFS", input_db, output_db);
+        assert!((output_db - TARGET_LEVEL_DB).abs() < 3.0, "settled at {:.1} dBFS", output_db);
+    }
+
+    #[test]
+    fn leaves_silence_alone() {
+        let mut samples = tone(RATE, 0.0005); // Below the gate
+        let original = samples.clone();
+        AutoGain::new().process(&mut samples);
+        assert_eq!(samples, original);
+    }
+
+    #[test]
+    fn limits_loud_input_without_clipping() {
+        // Quiet speech boosts the gain, then a loud burst arrives before it can come down
+        let mut samples = tone(3 * RATE, 0.01);
+        samples.extend(tone(RATE, 0.8));
+        let mut gain = AutoGain::new();
+        gain.process(&mut samples);
+
+        let loud = &samples[3 * RATE..];
+        let peak = loud.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak <= 1.0, "peak {} exceeds full scale", peak);
+        assert!(peak > LIMITER_THRESHOLD, "peak {} was never limited", peak);
+        // Once the gain has come down, the burst is turned down towards the target
+        assert!(level_db(&loud[RATE / 2..]) < level_db(&tone(RATE, 0.8)) - 6.0);
+    }
+}
@@ -0,0 +1,251 @@
+use realfft::num_complex::Complex;
+use realfft::{RealFftPlanner, RealToComplex};
+use serde::Serialize;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+// Levels are published this often
+const METER_INTERVAL_MS: u32 = 50;
+// Mono samples analyzed for the band energies
+const FFT_SIZE: usize = 512;
+const BAND_COUNT: usize = 8;
+const LOWEST_BAND_HZ: f32 = 100.0;
+const HIGHEST_BAND_HZ: f32 = 8000.0;
+const SILENCE_DB: f32 = -100.0;
+// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.99;
+// dBFS range mapped onto the 0.0 - 1.0 display level
+const DISPLAY_FLOOR_DB: f32 = -60.0;
+const DISPLAY_CEILING_DB: f32 = -10.0;
+
+/// One metering update, sent to the frontend as an `audio-level` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevels {
+    pub rms_db: f32,
+    pub peak_db: f32,
+    pub clipping: bool,
+    /// RMS mapped to 0.0 - 1.0 for simple meters.
+    pub level: f32,
+    /// Energy (dBFS) of log-spaced bands from 100 Hz up to 8 kHz.
+    pub bands: Vec<f32>,
+}
+
+/// Measures interleaved device audio over fixed intervals, across all channels.
+pub struct LevelMeter {
+    channels: usize,
+    interval_frames: usize,
+    frames: usize,
+    sum_squares: f64,
+    peak: f32,
+    clipping: bool,
+    // Most recent mono samples, oldest first
+    history: Vec<f32>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    window_power: f32,
+    time: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    // FFT bin range [start, end) of each band
+    bands: Vec<(usize, usize)>,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+        let spectrum = fft.make_output_vec();
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        let window_power = window.iter().map(|w| w * w).sum();
+
+        let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+        let top = HIGHEST_BAND_HZ.min(sample_rate as f32 / 2.0);
+        let ratio = (top / LOWEST_BAND_HZ).powf(1.0 / BAND_COUNT as f32);
+        let bands = (0..BAND_COUNT)
+            .map(|b| {
+                let low = LOWEST_BAND_HZ * ratio.powi(b as i32);
+                let start = (low / bin_hz).round() as usize;
+                let end = ((low * ratio / bin_hz).round() as usize).max(start + 1);
+                (start.min(spectrum.len() - 1), end.min(spectrum.len()))
+            })
+            .collect();
+
+        Self {
+            channels: channels.max(1) as usize,
+            interval_frames: (sample_rate * METER_INTERVAL_MS / 1000).max(1) as usize,
+            frames: 0,
+            sum_squares: 0.0,
+            peak: 0.0,
+            clipping: false,
+            history: vec![0.0; FFT_SIZE],
+            fft,
+            window,
+            window_power,
+            time: vec![0.0; FFT_SIZE],
+            spectrum,
+            bands,
+        }
+    }
+
+    /// Feed interleaved samples. Returns the levels once an interval is complete.
+    pub fn push(&mut self, interleaved: &[f32]) -> Option<AudioLevels> {
+        let frames = interleaved.len() / self.channels;
+        for sample in interleaved {
+            self.sum_squares += (*sample as f64) * (*sample as f64);
+            self.peak = self.peak.max(sample.abs());
+        }
+        self.clipping |= self.peak >= CLIP_LEVEL;
+        self.frames += frames;
+
+        // Keep the newest FFT_SIZE mono samples for the band analysis
+        let keep = frames.min(FFT_SIZE);
+        self.history.copy_within(keep.., 0);
+        let skip = frames - keep;
+        for (slot, frame) in self.history[FFT_SIZE - keep..]
+            .iter_mut()
+            .zip(interleaved.chunks_exact(self.channels).skip(skip))
+        {
+            *slot = frame.iter().sum::<f32>() / self.channels as f32;
+        }
+
+        if self.frames < self.interval_frames {
+            return None;
+        }
+        Some(self.take_levels())
+    }
+
+    fn take_levels(&mut self) -> AudioLevels {
+        let samples = (self.frames * self.channels).max(1);
+        let rms = (self.sum_squares / samples as f64).sqrt() as f32;
+        let rms_db = to_db(rms);
+        let levels = AudioLevels {
+            rms_db,
+            peak_db: to_db(self.peak),
+            clipping: self.clipping,
+            level: ((rms_db - DISPLAY_FLOOR_DB) / (DISPLAY_CEILING_DB - DISPLAY_FLOOR_DB)).clamp(0.0, 1.0),
+            bands: self.band_energies(),
+        };
+
+        self.frames = 0;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.clipping = false;
+        levels
+    }
+
+    fn band_energies(&mut self) -> Vec<f32> {
+        for ((t, x), w) in self.time.iter_mut().zip(&self.history).zip(&self.window) {
+            *t = x * w;
+        }
+        let _ = self.fft.process(&mut self.time, &mut self.spectrum);
+
+        // Parseval: scale bin power back to the power of the windowed signal
+        let scale = 2.0 / (FFT_SIZE as f32 * self.window_power);
+        self.bands
+            .iter()
+            .map(|&(start, end)| {
+                let power: f32 = self.spectrum[start..end].iter().map(|c| c.norm_sqr()).sum();
+                let db = 10.0 * (power * scale).log10();
+                if db.is_finite() { db.max(SILENCE_DB) } else { SILENCE_DB }
+            })
+            .collect()
+    }
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48000;
+    // 10 ms of stereo audio
+    const CHUNK_FRAMES: usize = 480;
+
+    /// Interleaved stereo sine, the same on both channels.
+    fn tone(frequency_hz: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let sample = amplitude * (2.0 * PI * frequency_hz * i as f32 / RATE as f32).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    /// Feed `samples` in 10 ms chunks and return every update.
+    fn measure(samples: &[f32]) -> Vec<AudioLevels> {
+        let mut meter = LevelMeter::new(RATE, 2);
+        samples.chunks(CHUNK_FRAMES * 2).filter_map(|chunk| meter.push(chunk)).collect()
+    }
+
+    fn loudest_band(levels: &AudioLevels) -> usize {
+        (0..BAND_COUNT).max_by(|&a, &b| levels.bands[a].total_cmp(&levels.bands[b])).unwrap()
+    }
+
+    #[test]
+    fn sine_level_and_peak() {
+        let levels = measure(&tone(1000.0, 0.5, RATE as usize / 10));
+        let levels = levels.last().unwrap();
+
+        // RMS of a sine is its amplitude over sqrt(2): -9.03 dBFS; peak -6.02 dBFS
+        assert!((levels.rms_db - -9.03).abs() < 0.05, "rms {} dB", levels.rms_db);
+        assert!((levels.peak_db - -6.02).abs() < 0.05, "peak {} dB", levels.peak_db);
+        assert!(!levels.clipping);
+        assert_eq!(levels.level, 1.0);
+    }
+
+    #[test]
+    fn silence_is_at_the_floor() {
+        let levels = measure(&vec![0.0; RATE as usize / 10]);
+        let levels = levels.last().unwrap();
+        assert_eq!(levels.rms_db, SILENCE_DB);
+        assert_eq!(levels.peak_db, SILENCE_DB);
+        assert_eq!(levels.level, 0.0);
+        assert!(levels.bands.iter().all(|&db| db == SILENCE_DB));
+    }
+
+    #[test]
+    fn full_scale_is_clipping() {
+        let mut samples = tone(1000.0, 1.0, RATE as usize / 20);
+        samples.extend(tone(1000.0, 0.5, RATE as usize / 20));
+        let levels = measure(&samples);
+
+        assert!(levels[0].clipping);
+        assert!(levels[0].peak_db > -0.1, "peak {} dB", levels[0].peak_db);
+        // The flag only covers its own interval
+        assert!(!levels[1].clipping);
+    }
+
+    #[test]
+    fn tone_energy_lands_in_its_band() {
+        // Bands start at 100 Hz and grow by 80^(1/8) (about 1.73x): 1 kHz falls
+        // in 892 - 1542 Hz, 3 kHz in 2666 - 4608 Hz
+        for (frequency_hz, band) in [(1000.0, 4), (3000.0, 6)] {
+            let levels = measure(&tone(frequency_hz, 0.5, RATE as usize / 10));
+            let levels = levels.last().unwrap();
+
+            assert_eq!(loudest_band(levels), band, "{} Hz: {:?}", frequency_hz, levels.bands);
+            // The band holds (nearly) all of the tone's power
+            assert!((levels.bands[band] - levels.rms_db).abs() < 1.0, "{} Hz: {:?}", frequency_hz, levels.bands);
+            for (other, db) in levels.bands.iter().enumerate() {
+                if other.abs_diff(band) > 1 {
+                    assert!(*db < levels.rms_db - 40.0, "{} Hz, band {}: {} dB", frequency_hz, other, db);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn updates_every_50_ms() {
+        let mut meter = LevelMeter::new(RATE, 2);
+        let chunk = tone(440.0, 0.3, CHUNK_FRAMES);
+        let updates: Vec<usize> = (1..=20).filter(|_| meter.push(&chunk).is_some()).collect();
+        assert_eq!(updates, [5, 10, 15, 20]);
+    }
+}
@@ -1,2 +1,22 @@
+pub mod archive;
+pub mod decode;
+pub mod denoise;
+pub mod devices;
+pub mod downmix;
+pub mod encoder;
+pub mod events;
+pub mod gain;
+pub mod meter;
+pub mod pipeline;
+pub mod preroll;
 pub mod recorder;
-pub use recorder::AudioRecorder;
+pub mod resample;
+pub mod source;
+pub mod vad;
+pub use devices::{list_input_devices, list_loopback_devices, InputDeviceInfo};
+pub use events::RecorderEvent;
+pub use pipeline::AudioTap;
+pub use recorder::{record_source, AudioRecorder, RecordingOptions};
+pub use source::{CaptureStats, SourceKind};
+
+pub const TARGET_SAMPLE_RATE: u32 = 16000; // Optimal for Azure Speech Service
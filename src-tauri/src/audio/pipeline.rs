@@ -0,0 +1,246 @@
+use super::denoise::Denoiser;
+use super::downmix::{ChannelMix, Downmixer};
+use super::encoder::{EncoderSettings, StreamEncoder};
+use super::gain::{AutoGain, LevelMonitor};
+use super::resample::Resampler;
+use super::TARGET_SAMPLE_RATE;
+
+// Minimum recording duration (0.5 seconds at 16kHz)
+const MIN_SAMPLES: u64 = 8000;
+
+/// Receives the processed 16 kHz mono audio as it is encoded, e.g. for
+/// streaming recognition. Dropped when the recording finishes.
+pub type AudioTap = tokio::sync::mpsc::UnboundedSender<Vec<f32>>;
+
+/// Optional processing stages, set from the audio settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineOptions {
+    /// Keep an untrimmed 16 kHz PCM copy (after noise suppression) alongside the Opus stream.
+    pub keep_lossless: bool,
+    /// Noise suppression strength (0.0 - 1.0), `None` to leave the signal untouched.
+    pub noise_suppression: Option<f32>,
+    /// Normalize the speech level before encoding.
+    pub auto_gain: bool,
+    pub encoder: EncoderSettings,
+    /// How the device's channels are reduced to mono.
+    pub channel_mix: ChannelMix,
+}
+
+/// A finished recording: the encoded audio to upload (OGG/Opus or WAV), plus
+/// the untrimmed 16 kHz PCM when a lossless copy was asked for.
+pub struct FinishedAudio {
+    pub encoded: Vec<u8>,
+    pub lossless: Option<Vec<i16>>,
+    /// Problems with the input level worth telling the user about.
+    pub warnings: Vec<String>,
+}
+
+/// Per-recording processing chain, run on the capture thread as audio arrives:
+/// interleaved device samples -> mono -> 16 kHz -> (denoise) -> (AGC) -> Opus/PCM packets.
+pub struct CapturePipeline {
+    channels: usize,
+    source_rate: u32,
+    downmix: Downmixer,
+    resampler: Resampler,
+    encoder: StreamEncoder,
+    // Samples of an incomplete interleaved frame carried over to the next chunk
+    carry: Vec<f32>,
+    mono: Vec<f32>,
+    resampled: Vec<f32>,
+    denoiser: Option<Denoiser>,
+    denoised: Vec<f32>,
+    gain: Option<AutoGain>,
+    levels: LevelMonitor,
+    input_samples: u64,
+    // Untrimmed 16 kHz PCM kept alongside the Opus stream when a lossless copy was asked for
+    lossless: Option<Vec<i16>>,
+    tap: Option<AudioTap>,
+}
+
+impl CapturePipeline {
+    pub fn new(source_rate: u32, channels: u16, options: &PipelineOptions) -> Result<Self, String> {
+        if source_rate != TARGET_SAMPLE_RATE {
+            println!(">>> Resampling from {} Hz to {} Hz", source_rate, TARGET_SAMPLE_RATE);
+        }
+
+        if let Some(strength) = options.noise_suppression {
+            println!(">>> Noise suppression enabled (strength {:.2})", strength);
+        }
+
+        Ok(Self {
+            channels: channels.max(1) as usize,
+            source_rate,
+            downmix: Downmixer::new(options.channel_mix, channels, source_rate),
+            resampler: Resampler::new(source_rate, TARGET_SAMPLE_RATE),
+            encoder: StreamEncoder::new(&options.encoder)?,
+            carry: Vec::new(),
+            mono: Vec::new(),
+            resampled: Vec::new(),
+            denoiser: options.noise_suppression.map(Denoiser::new),
+            denoised: Vec::new(),
+            gain: options.auto_gain.then(AutoGain::new),
+            levels: LevelMonitor::default(),
+            input_samples: 0,
+            lossless: options.keep_lossless.then(Vec::new),
+            tap: None,
+        })
+    }
+
+    /// Send the processed audio to `tap` as well as the encoder.
+    pub fn set_tap(&mut self, tap: Option<AudioTap>) {
+        self.tap = tap;
+    }
+
+    /// Length of the audio processed so far, in seconds.
+    pub fn duration_secs(&self) -> f32 {
+        self.encoder.sample_count() as f32 / TARGET_SAMPLE_RATE as f32
+    }
+
+    /// Size of the audio held in memory so far (encoded packets plus any lossless copy).
+    pub fn encoded_bytes(&self) -> usize {
+        let lossless = self.lossless.as_ref().map_or(0, |pcm| pcm.len() * 2);
+        self.encoder.encoded_bytes() + lossless
+    }
+
+    /// Denoise and normalize the freshly resampled audio (if enabled), keep the
+    /// lossless copy and encode it. `flush` drains the denoiser at the end of the stream.
+    fn encode_resampled(&mut self, flush: bool) -> Result<(), String> {
+        let samples = match self.denoiser.as_mut() {
+            Some(denoiser) => {
+                self.denoised.clear();
+                denoiser.process(&self.resampled, &mut self.denoised);
+                if flush {
+                    denoiser.flush(&mut self.denoised);
+                }
+                &mut self.denoised
+            }
+            None => &mut self.resampled,
+        };
+
+        // Judge the input level before AGC hides it
+        self.levels.measure(samples);
+        if let Some(ref mut gain) = self.gain {
+            gain.process(samples);
+        }
+
+        if let Some(ref mut pcm) = self.lossless {
+            pcm.extend(samples.iter().map(|s| (s * i16::MAX as f32) as i16));
+        }
+        if !samples.is_empty() && self.tap.as_ref().is_some_and(|tap| tap.send(samples.to_vec()).is_err()) {
+            // The receiver gave up (e.g. the connection failed), keep recording without it
+            self.tap = None;
+        }
+        self.encoder.push(samples)
+    }
+
+    /// Feed a chunk of interleaved samples straight from the device.
+    pub fn push(&mut self, interleaved: &[f32]) -> Result<(), String> {
+        self.input_samples += interleaved.len() as u64;
+        self.levels.check_clipping(interleaved);
+
+        // Convert to mono, keeping any partial frame for the next chunk
+        self.mono.clear();
+        let data: &[f32] = if self.carry.is_empty() {
+            interleaved
+        } else {
+            self.carry.extend_from_slice(interleaved);
+            &self.carry
+        };
+        let whole = data.len() - data.len() % self.channels;
+        self.downmix.process(&data[..whole], &mut self.mono);
+        let rest = data[whole..].to_vec();
+        self.carry = rest;
+
+        // Resample to target sample rate
+        self.resampled.clear();
+        self.resampler.process(&self.mono, &mut self.resampled);
+
+        self.encode_resampled(false)
+    }
+
+    /// Continue the recording with audio from a different device (e.g. after
+    /// the original one was unplugged). Audio already pushed is kept.
+    pub fn switch_source(&mut self, source_rate: u32, channels: u16, channel_mix: ChannelMix) -> Result<(), String> {
+        println!(
+            ">>> Switching pipeline input from {} Hz/{} ch to {} Hz/{} ch",
+            self.source_rate, self.channels, source_rate, channels
+        );
+
+        // Drain the old resampler; a partial frame from the old device is dropped
+        self.resampled.clear();
+        self.resampler.flush(&mut self.resampled);
+        self.encode_resampled(false)?;
+
+        self.resampler = Resampler::new(source_rate, TARGET_SAMPLE_RATE);
+        self.downmix = Downmixer::new(channel_mix, channels, source_rate);
+        self.source_rate = source_rate;
+        self.channels = channels.max(1) as usize;
+        self.carry.clear();
+        Ok(())
+    }
+
+    /// Flush the resampler and close the encoded stream.
+    pub fn finish(mut self, trim_silence: bool) -> Result<FinishedAudio, String> {
+        log::info!(
+            "Recording stopped. Captured {} samples at {} Hz, {} channels",
+            self.input_samples,
+            self.source_rate,
+            self.channels
+        );
+        println!(
+            ">>> Recording stopped. Captured {} samples at {} Hz, {} channels",
+            self.input_samples,
+            self.source_rate,
+            self.channels
+        );
+
+        // Check if we got any data at all
+        if self.input_samples == 0 {
+            println!(">>> ERROR: No audio data captured!");
+            log::error!("No audio data captured!");
+            return Err("No audio data captured. Microphone may not be working.".to_string());
+        }
+
+        self.resampled.clear();
+        self.resampler.flush(&mut self.resampled);
+        self.encode_resampled(true)?;
+        if let Some(ref denoiser) = self.denoiser {
+            denoiser.log_snr();
+        }
+
+        let total = self.encoder.sample_count();
+        let duration = total as f32 / TARGET_SAMPLE_RATE as f32;
+        log::info!("Resampled to {} samples at {} Hz", total, TARGET_SAMPLE_RATE);
+        println!(">>> Resampled to {} samples at {} Hz (duration: {:.2}s)",
+            total,
+            TARGET_SAMPLE_RATE,
+            duration
+        );
+
+        if total < MIN_SAMPLES {
+            log::warn!(
+                "Recording too short: {} samples (minimum {}). Duration: {:.2}s",
+                total,
+                MIN_SAMPLES,
+                duration
+            );
+            println!(
+                ">>> WARNING: Recording too short: {} samples (minimum {}). Duration: {:.2}s",
+                total,
+                MIN_SAMPLES,
+                duration
+            );
+            return Err(format!(
+                "Recording too short ({:.1}s). Please hold the key longer.",
+                duration
+            ));
+        }
+
+        println!(">>> Closing encoded stream...");
+        Ok(FinishedAudio {
+            encoded: self.encoder.finish(trim_silence)?,
+            lossless: self.lossless,
+            warnings: self.levels.warnings(),
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+
+/// Ring buffer holding the most recent interleaved audio while the
+/// microphone is kept warm between recordings.
+pub struct PreRollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    channels: usize,
+}
+
+impl PreRollBuffer {
+    pub fn new(sample_rate: u32, channels: u16, duration_ms: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let frames = (sample_rate as u64 * duration_ms as u64 / 1000) as usize;
+        let capacity = frames * channels;
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            channels,
+        }
+    }
+
+    pub fn push(&mut self, data: &[f32]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if data.len() >= self.capacity {
+            // Only the tail of this chunk fits, keep it frame-aligned
+            let keep = self.capacity - self.capacity % self.channels;
+            self.samples.clear();
+            self.samples.extend(&data[data.len() - keep..]);
+            return;
+        }
+
+        self.samples.extend(data);
+        if self.samples.len() > self.capacity {
+            let excess = self.samples.len() - self.capacity;
+            // Drop whole frames so channels stay interleaved correctly
+            let drop = excess.div_ceil(self.channels) * self.channels;
+            self.samples.drain(..drop.min(self.samples.len()));
+        }
+    }
+
+    /// Remove and return the buffered audio, oldest first.
+    pub fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+}
@@ -1,21 +1,174 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{FromSample, SampleFormat};
-use std::io::Cursor;
+use cpal::traits::HostTrait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::thread;
-use tokio::sync::oneshot;
-use audiopus::{coder::Encoder, Application, Channels, SampleRate};
-use ogg::writing::PacketWriteEndInfo;
+use std::time::Duration;
+
+use super::downmix::ChannelMix;
+use super::encoder::{EncoderSettings, UploadFormat};
+use super::events::{EventHandler, PauseState, RecorderEvent, RecordingError, RecordingLimit, SilenceCountdown};
+use super::meter::LevelMeter;
+use super::pipeline::{AudioTap, CapturePipeline, FinishedAudio, PipelineOptions};
+use super::preroll::PreRollBuffer;
+use super::source::{AudioSource, CaptureStats, SourceKind};
+use super::vad::{frame_energy_db, SilenceStatus, SilenceTimer};
+use crate::config::AppConfig;
+
+// How often the capture thread pulls audio from the source into the pipeline
+const DRAIN_INTERVAL: Duration = Duration::from_millis(20);
+// How long stop_recording waits for the capture thread to close the stream
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+// Hard cap on encoded audio kept in memory, whatever the duration limit
+const MAX_ENCODED_BYTES: usize = 64 * 1024 * 1024;
+// Attempts to reopen capture on the system default device after a failure
+const RECONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+// Hands-free countdown events are sent when the remaining time crosses a step of this size
+const COUNTDOWN_STEP_MS: u32 = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct RecordingOptions {
+    pub source: SourceKind,
+    pub trim_silence: bool,
+    pub warm_microphone: bool,
+    pub pre_roll_ms: u32,
+    pub max_duration_secs: u32,
+    /// Hands-free: stop by itself after this much silence following speech.
+    pub silence_timeout_ms: Option<u32>,
+    /// Continue on the system default device if the microphone fails mid-recording.
+    pub reconnect: bool,
+    /// Downmix per device name; devices not listed average their channels.
+    pub channel_mixes: HashMap<String, ChannelMix>,
+    pub pipeline: PipelineOptions,
+    /// Also receives the processed audio while recording (streaming recognition).
+    pub audio_tap: Option<AudioTap>,
+}
+
+impl RecordingOptions {
+    fn channel_mix(&self, device: &str) -> ChannelMix {
+        self.channel_mixes.get(device).copied().unwrap_or_default()
+    }
+}
+
+impl From<&AppConfig> for RecordingOptions {
+    fn from(app_config: &AppConfig) -> Self {
+        let config = &app_config.audio;
+        let source = match config.capture_source.as_str() {
+            "system" => SourceKind::Loopback { device_name: config.loopback_device.clone() },
+            "both" => SourceKind::Mixed {
+                microphone: config.input_device.clone(),
+                loopback: config.loopback_device.clone(),
+            },
+            _ => SourceKind::Microphone { device_name: config.input_device.clone() },
+        };
+        let mut encoder = EncoderSettings::from(&app_config.encoding);
+        if app_config.provider == "whisper" {
+            // Local Whisper decodes the upload again, skip the lossy Opus round trip
+            encoder.format = UploadFormat::Wav;
+        }
+        Self {
+            source,
+            trim_silence: config.trim_silence,
+            warm_microphone: config.warm_microphone,
+            pre_roll_ms: config.pre_roll_ms,
+            max_duration_secs: if config.hands_free {
+                config.max_recording_secs.min(config.hands_free_max_secs).max(1)
+            } else {
+                config.max_recording_secs.max(1)
+            },
+            silence_timeout_ms: config.hands_free.then_some(config.silence_timeout_ms),
+            reconnect: config.reconnect_on_disconnect,
+            channel_mixes: config
+                .channel_modes
+                .iter()
+                .map(|(device, mode)| (device.clone(), ChannelMix::from_name(mode)))
+                .collect(),
+            pipeline: PipelineOptions {
+                keep_lossless: config.keep_lossless_copy,
+                noise_suppression: config
+                    .noise_suppression
+                    .then_some(config.noise_suppression_strength),
+                auto_gain: config.auto_gain,
+                encoder,
+                channel_mix: ChannelMix::default(),
+            },
+            audio_tap: None,
+        }
+    }
+}
+
+// Commands to send to the capture thread
+enum CaptureCommand {
+    Begin(RecordingOptions),
+    SetPaused(bool),
+    Stop(mpsc::Sender<Result<Vec<u8>, String>>),
+    Shutdown,
+}
 
-const TARGET_SAMPLE_RATE: u32 = 16000; // Optimal for Azure Speech Service
-const OPUS_FRAME_SIZE: usize = 960; // 60ms at 16kHz (recommended for voice)
+// Handle to a running capture thread. A cold session lives for a single
+// recording; a warm one keeps the stream open between recordings.
+struct CaptureSession {
+    commands: mpsc::Sender<CaptureCommand>,
+    source: SourceKind,
+    channel_mixes: HashMap<String, ChannelMix>,
+    warm: bool,
+    pre_roll_ms: u32,
+}
+
+impl CaptureSession {
+    fn spawn(options: &RecordingOptions, context: SessionShared) -> Self {
+        let (command_tx, command_rx) = mpsc::channel::<CaptureCommand>();
+
+        let context = CaptureContext {
+            commands: command_rx,
+            source: options.source.clone(),
+            channel_mixes: options.channel_mixes.clone(),
+            warm: options.warm_microphone,
+            pre_roll_ms: options.pre_roll_ms,
+            reconnect: options.reconnect,
+            shared: context,
+        };
+
+        // Spawn a dedicated thread for audio recording
+        // This avoids the Send requirement since the Stream stays in this thread
+        thread::spawn(move || run_capture(context));
+
+        Self {
+            commands: command_tx,
+            source: options.source.clone(),
+            channel_mixes: options.channel_mixes.clone(),
+            warm: options.warm_microphone,
+            pre_roll_ms: options.pre_roll_ms,
+        }
+    }
+
+    fn matches(&self, options: &RecordingOptions) -> bool {
+        self.warm == options.warm_microphone
+            && self.source == options.source
+            && self.channel_mixes == options.channel_mixes
+            && self.pre_roll_ms == options.pre_roll_ms
+    }
+
+    fn shutdown(self) {
+        let _ = self.commands.send(CaptureCommand::Shutdown);
+    }
+}
 
 pub struct AudioRecorder {
-    buffer: Arc<StdMutex<Vec<f32>>>,
     is_recording: Arc<StdMutex<bool>>,
-    source_sample_rate: Arc<StdMutex<u32>>,
-    source_channels: Arc<StdMutex<u16>>,
-    stop_sender: Option<oneshot::Sender<()>>,
+    // Latest level (f32 bits) published by the capture thread
+    level: Arc<AtomicU32>,
+    warnings: Arc<StdMutex<Vec<String>>>,
+    // 16 kHz PCM of the last recording, when a lossless copy was asked for
+    lossless: Arc<StdMutex<Option<Vec<i16>>>>,
+    // Overruns of the current input stream
+    stats: Arc<StdMutex<CaptureStats>>,
+    // Label of the source the last recording was started from
+    last_source: Option<&'static str>,
+    events: Option<EventHandler>,
+    session: Option<CaptureSession>,
 }
 
 // Manually implement Send + Sync since we're not storing the Stream anymore
@@ -30,558 +183,956 @@ impl AudioRecorder {
             .default_input_device()
             .ok_or("No input device available")?;
 
-        Ok(Self {
-            buffer: Arc::new(StdMutex::new(Vec::new())),
-            is_recording: Arc::new(StdMutex::new(false)),
-            source_sample_rate: Arc::new(StdMutex::new(TARGET_SAMPLE_RATE)),
-            source_channels: Arc::new(StdMutex::new(1)),
-            stop_sender: None,
-        })
+        Ok(Self::new_dummy())
     }
 
     /// Create a dummy recorder when no audio input device is available.
     /// Recording will fail at runtime but the app can still start.
     pub fn new_dummy() -> Self {
         Self {
-            buffer: Arc::new(StdMutex::new(Vec::new())),
             is_recording: Arc::new(StdMutex::new(false)),
-            source_sample_rate: Arc::new(StdMutex::new(TARGET_SAMPLE_RATE)),
-            source_channels: Arc::new(StdMutex::new(1)),
-            stop_sender: None,
+            level: Arc::new(AtomicU32::new(0)),
+            warnings: Arc::new(StdMutex::new(Vec::new())),
+            lossless: Arc::new(StdMutex::new(None)),
+            stats: Arc::new(StdMutex::new(CaptureStats::default())),
+            last_source: None,
+            events: None,
+            session: None,
         }
     }
 
-    pub fn start_recording(&mut self) -> Result<(), String> {
-        // Check if already recording - stop previous recording first
-        {
-            let is_recording = self.is_recording.lock().unwrap();
-            if *is_recording {
-                log::warn!("start_recording called but already recording - stopping previous recording first");
-                println!(">>> WARN: start_recording called but already recording");
-                drop(is_recording); // Release lock before stopping
-
-                // Send stop signal to previous recording
-                if let Some(sender) = self.stop_sender.take() {
-                    let _ = sender.send(());
-                }
+    /// Install the handler that receives events raised while recording.
+    /// Only sessions started afterwards pick it up.
+    pub fn set_event_handler(&mut self, handler: EventHandler) {
+        self.events = Some(handler);
+    }
 
-                // Wait for previous recording to stop
-                std::thread::sleep(std::time::Duration::from_millis(150));
+    fn shared(&self) -> SessionShared {
+        SessionShared {
+            is_recording: Arc::clone(&self.is_recording),
+            level: Arc::clone(&self.level),
+            warnings: Arc::clone(&self.warnings),
+            lossless: Arc::clone(&self.lossless),
+            stats: Arc::clone(&self.stats),
+            events: self.events.clone(),
+        }
+    }
 
-                // Force reset the flag
-                let mut is_rec = self.is_recording.lock().unwrap();
-                *is_rec = false;
-            }
+    /// Open or close the warm microphone to match `options`. While warm, the
+    /// input stream stays open between recordings and the last
+    /// `pre_roll_ms` of audio is prepended to each new recording.
+    pub fn apply_warm_microphone(&mut self, options: &RecordingOptions) {
+        if *self.is_recording.lock().unwrap() {
+            // Applied at the next start_recording instead
+            return;
         }
 
-        // Clear previous buffer
-        {
-            let mut buffer = self.buffer.lock().unwrap();
-            let prev_len = buffer.len();
-            buffer.clear();
-            log::info!("Cleared previous buffer ({} samples)", prev_len);
-            println!(">>> Cleared previous buffer ({} samples)", prev_len);
+        if self.session.as_ref().is_some_and(|s| s.matches(options)) {
+            return;
+        }
+
+        if let Some(session) = self.session.take() {
+            session.shutdown();
+        }
+
+        if options.warm_microphone {
+            log::info!("Warming up microphone ({} ms pre-roll)", options.pre_roll_ms);
+            println!(">>> Warming up microphone ({} ms pre-roll)", options.pre_roll_ms);
+            self.session = Some(CaptureSession::spawn(options, self.shared()));
         }
+    }
 
-        // Clear any pending stop sender
-        self.stop_sender = None;
+    /// Start recording from `options.source`.
+    pub fn start_recording(&mut self, options: RecordingOptions) -> Result<(), String> {
+        // Check if already recording - stop previous recording first
+        let was_recording = *self.is_recording.lock().unwrap();
+        if was_recording {
+            log::warn!("start_recording called but already recording - stopping previous recording first");
+            println!(">>> WARN: start_recording called but already recording");
 
-        log::info!("Starting recording...");
-        println!(">>> Starting recording...");
+            // Stop the previous recording and discard its audio
+            let _ = self.stop_recording();
 
-        let buffer = Arc::clone(&self.buffer);
-        let is_recording = Arc::clone(&self.is_recording);
-        let source_sample_rate = Arc::clone(&self.source_sample_rate);
-        let source_channels = Arc::clone(&self.source_channels);
+            // Force reset the flag
+            *self.is_recording.lock().unwrap() = false;
+        }
+
+        // Clear warnings and audio left over from the previous recording
+        self.warnings.lock().unwrap().clear();
+        *self.lossless.lock().unwrap() = None;
+        self.level.store(0, Ordering::Relaxed);
+        self.last_source = Some(options.source.label());
+
+        log::info!("Starting recording from {}...", options.source.label());
+        println!(">>> Starting recording from {}...", options.source.label());
 
         // Set recording flag
         {
-            let mut recording = is_recording.lock().unwrap();
+            let mut recording = self.is_recording.lock().unwrap();
             *recording = true;
             log::info!("Recording flag set to true");
             println!(">>> Recording flag set to true");
         }
 
-        let (stop_tx, stop_rx) = oneshot::channel::<()>();
-        self.stop_sender = Some(stop_tx);
+        let begin = || CaptureCommand::Begin(options.clone());
 
-        // Spawn a dedicated thread for audio recording
-        // This avoids the Send requirement since the Stream stays in this thread
-        thread::spawn(move || {
-            println!(">>> Recording thread started");
-            log::info!("Recording thread started");
-
-            let host = cpal::default_host();
-            println!(">>> Audio host: {:?}", host.id());
-
-            let device = match host.default_input_device() {
-                Some(d) => d,
-                None => {
-                    log::error!("No input device available");
-                    println!(">>> ERROR: No input device available");
-                    if let Ok(mut recording) = is_recording.lock() {
-                        *recording = false;
-                    }
-                    return;
-                }
-            };
-
-            let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
-            log::info!("Using audio input device: {}", device_name);
-            println!(">>> Using audio input device: {}", device_name);
-
-            let supported_config = match device.default_input_config() {
-                Ok(c) => c,
-                Err(e) => {
-                    log::error!("Failed to get default input config: {}", e);
-                    println!(">>> ERROR: Failed to get default input config: {}", e);
-                    if let Ok(mut recording) = is_recording.lock() {
-                        *recording = false;
-                    }
-                    return;
-                }
-            };
+        // Reuse the warm session if it is still alive and configured the same way
+        if let Some(session) = self.session.take() {
+            if session.matches(&options) && session.commands.send(begin()).is_ok() {
+                println!(">>> Using warm microphone");
+                self.session = Some(session);
+                return Ok(());
+            }
+            session.shutdown();
+        }
 
-            // Use the device's native config (don't override sample rate/channels)
-            let config = supported_config.config();
+        let session = CaptureSession::spawn(&options, self.shared());
+        session
+            .commands
+            .send(begin())
+            .map_err(|_| "Recording thread exited unexpectedly".to_string())?;
+        self.session = Some(session);
 
-            // Store the actual sample rate and channels for resampling later
-            if let Ok(mut rate) = source_sample_rate.lock() {
-                *rate = config.sample_rate.0;
-            }
-            if let Ok(mut ch) = source_channels.lock() {
-                *ch = config.channels;
-            }
+        Ok(())
+    }
 
-            log::info!(
-                "Audio config: {} Hz, {} channels, format: {:?}",
-                config.sample_rate.0,
-                config.channels,
-                supported_config.sample_format()
-            );
-            println!(
-                ">>> Audio config: {} Hz, {} channels, format: {:?}",
-                config.sample_rate.0,
-                config.channels,
-                supported_config.sample_format()
-            );
-
-            let err_fn = |err| {
-                log::error!("Stream error: {}", err);
-                println!(">>> Stream error: {}", err);
-            };
-
-            let buffer_for_callback = buffer.clone();
-            let sample_counter = Arc::new(StdMutex::new(0usize));
-            let sample_counter_for_callback = sample_counter.clone();
-
-            let stream = match supported_config.sample_format() {
-                SampleFormat::F32 => {
-                    build_stream_with_logging::<f32>(&device, &config, buffer_for_callback, sample_counter_for_callback, err_fn)
-                }
-                SampleFormat::I16 => {
-                    build_stream_with_logging::<i16>(&device, &config, buffer_for_callback, sample_counter_for_callback, err_fn)
-                }
-                SampleFormat::U16 => {
-                    build_stream_with_logging::<u16>(&device, &config, buffer_for_callback, sample_counter_for_callback, err_fn)
-                }
-                sample_format => {
-                    log::error!("Unsupported sample format: {}", sample_format);
-                    println!(">>> ERROR: Unsupported sample format: {}", sample_format);
-                    if let Ok(mut recording) = is_recording.lock() {
-                        *recording = false;
-                    }
-                    return;
-                }
-            };
-
-            let stream = match stream {
-                Ok(s) => s,
-                Err(e) => {
-                    log::error!("Failed to build stream: {}", e);
-                    println!(">>> ERROR: Failed to build stream: {}", e);
-                    if let Ok(mut recording) = is_recording.lock() {
-                        *recording = false;
-                    }
-                    return;
-                }
-            };
+    pub fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
+        log::info!("stop_recording called");
+        println!(">>> stop_recording called");
 
-            if let Err(e) = stream.play() {
-                log::error!("Failed to play stream: {}", e);
-                println!(">>> ERROR: Failed to play stream: {}", e);
-                if let Ok(mut recording) = is_recording.lock() {
-                    *recording = false;
+        let result = match self.session.take() {
+            Some(session) => {
+                println!(">>> Sending stop signal to recording thread");
+                let (reply_tx, reply_rx) = mpsc::channel();
+                let result = if session.commands.send(CaptureCommand::Stop(reply_tx)).is_ok() {
+                    reply_rx
+                        .recv_timeout(STOP_TIMEOUT)
+                        .unwrap_or_else(|_| Err("Recording thread did not respond".to_string()))
+                } else {
+                    Err("No audio data captured. Microphone may not be working.".to_string())
+                };
+                // Cold sessions end with the recording; warm ones stay open
+                if session.warm {
+                    self.session = Some(session);
                 }
-                return;
+                result
             }
+            None => {
+                println!(">>> WARNING: No stop sender available - recording may not have started properly");
+                log::warn!("No stop sender available - recording may not have started properly");
+                Err("No audio data captured. Microphone may not be working.".to_string())
+            }
+        };
 
-            log::info!("Recording started - stream is playing");
-            println!(">>> Recording started - stream is playing");
+        // Set recording flag to false
+        {
+            let mut recording = self.is_recording.lock().unwrap();
+            log::info!("Setting is_recording to false (was: {})", *recording);
+            println!(">>> Setting is_recording to false (was: {})", *recording);
+            *recording = false;
+        }
+        self.level.store(0, Ordering::Relaxed);
 
-            // Block until we receive the stop signal
-            let _ = stop_rx.blocking_recv();
+        if let Ok(ref data) = result {
+            println!(">>> Encoded successfully: {} bytes", data.len());
+        }
+        result
+    }
 
-            // Log final sample count
-            if let Ok(count) = sample_counter.lock() {
-                println!(">>> Recording stopping - total samples collected in callbacks: {}", *count);
-                log::info!("Recording stopping - total samples collected in callbacks: {}", *count);
-            }
+    /// Take the warnings collected during the last recording (e.g. device fallback).
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
 
-            // Stream is dropped here, stopping the recording
-            drop(stream);
+    /// Take the lossless 16 kHz copy of the last recording, if one was kept.
+    pub fn take_lossless_copy(&self) -> Option<Vec<i16>> {
+        self.lossless.lock().unwrap().take()
+    }
 
-            // Set recording flag to false
-            if let Ok(mut recording) = is_recording.lock() {
-                *recording = false;
-            }
+    /// Stop appending audio to the current recording. The session and input
+    /// stream stay open, so `resume_recording` continues the same recording.
+    pub fn pause_recording(&mut self) -> Result<(), String> {
+        self.set_paused(true)
+    }
 
-            log::info!("Recording thread stopped");
-            println!(">>> Recording thread stopped");
-        });
+    /// Continue a paused recording.
+    pub fn resume_recording(&mut self) -> Result<(), String> {
+        self.set_paused(false)
+    }
 
-        Ok(())
+    fn set_paused(&mut self, paused: bool) -> Result<(), String> {
+        if !*self.is_recording.lock().unwrap() {
+            return Err("Not recording".to_string());
+        }
+        let session = self.session.as_ref().ok_or("Not recording")?;
+        session
+            .commands
+            .send(CaptureCommand::SetPaused(paused))
+            .map_err(|_| "Recording thread exited unexpectedly".to_string())
     }
 
-    pub fn stop_recording(&mut self) -> Result<Vec<u8>, String> {
-        log::info!("stop_recording called");
-        println!(">>> stop_recording called");
+    /// Where the last recording's audio came from, see `SourceKind::label`.
+    pub fn last_source(&self) -> Option<&'static str> {
+        self.last_source
+    }
 
-        // Send stop signal
-        if let Some(sender) = self.stop_sender.take() {
-            println!(">>> Sending stop signal to recording thread");
-            let _ = sender.send(());
-        } else {
-            println!(">>> WARNING: No stop sender available - recording may not have started properly");
-            log::warn!("No stop sender available - recording may not have started properly");
+    /// Buffer overruns of the current input stream, for diagnostics.
+    pub fn capture_stats(&self) -> CaptureStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Latest display level (0.0 - 1.0). The frontend gets the full metering from `audio-level` events.
+    pub fn get_audio_level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+}
+
+// State shared between the recorder and its capture threads
+struct SessionShared {
+    is_recording: Arc<StdMutex<bool>>,
+    level: Arc<AtomicU32>,
+    warnings: Arc<StdMutex<Vec<String>>>,
+    lossless: Arc<StdMutex<Option<Vec<i16>>>>,
+    stats: Arc<StdMutex<CaptureStats>>,
+    events: Option<EventHandler>,
+}
+
+// Everything the capture thread needs, moved into it at start
+struct CaptureContext {
+    commands: mpsc::Receiver<CaptureCommand>,
+    source: SourceKind,
+    // Needed when opening the source; a mixed source downmixes the microphone itself
+    channel_mixes: HashMap<String, ChannelMix>,
+    warm: bool,
+    pre_roll_ms: u32,
+    reconnect: bool,
+    shared: SessionShared,
+}
+
+impl CaptureContext {
+    fn set_recording(&self, value: bool) {
+        if let Ok(mut recording) = self.shared.is_recording.lock() {
+            *recording = value;
         }
+    }
 
-        // Wait a bit for the thread to stop
-        std::thread::sleep(std::time::Duration::from_millis(100));
+    fn warn(&self, warning: &str) {
+        if let Ok(mut w) = self.shared.warnings.lock() {
+            w.push(warning.to_string());
+        }
+    }
 
-        // Set recording flag to false
-        {
-            let mut recording = self.is_recording.lock().unwrap();
-            log::info!("Setting is_recording to false (was: {})", *recording);
-            println!(">>> Setting is_recording to false (was: {})", *recording);
-            *recording = false;
+    fn update_stats(&self, stats: CaptureStats) {
+        if let Ok(mut current) = self.shared.stats.lock() {
+            *current = stats;
         }
+    }
 
-        // Get buffer data and source info
-        let buffer_data = {
-            let buffer = self.buffer.lock().unwrap();
-            println!(">>> Buffer contains {} samples", buffer.len());
-            buffer.clone()
-        };
+    /// Warn if audio was dropped between `since` and `now`.
+    fn check_overflows(&self, since: CaptureStats, now: CaptureStats) {
+        let overflows = now.overflows.saturating_sub(since.overflows);
+        if overflows == 0 {
+            return;
+        }
+        let dropped = now.dropped_samples.saturating_sub(since.dropped_samples);
+        log::warn!("Input buffer overflowed {} times, {} samples dropped", overflows, dropped);
+        println!(">>> WARNING: Input buffer overflowed {} times, {} samples dropped", overflows, dropped);
+        self.warn("Some audio was dropped because the system was too busy to keep up");
+    }
 
-        let source_rate = *self.source_sample_rate.lock().unwrap();
-        let source_channels = *self.source_channels.lock().unwrap();
-
-        log::info!(
-            "Recording stopped. Captured {} samples at {} Hz, {} channels",
-            buffer_data.len(),
-            source_rate,
-            source_channels
-        );
-        println!(
-            ">>> Recording stopped. Captured {} samples at {} Hz, {} channels",
-            buffer_data.len(),
-            source_rate,
-            source_channels
-        );
-
-        // Check if we got any data at all
-        if buffer_data.is_empty() {
-            println!(">>> ERROR: No audio data captured!");
-            log::error!("No audio data captured!");
-            return Err("No audio data captured. Microphone may not be working.".to_string());
-        }
-
-        // Convert to mono if needed
-        let mono_data: Vec<f32> = if source_channels > 1 {
-            println!(">>> Converting {} channels to mono", source_channels);
-            buffer_data
-                .chunks(source_channels as usize)
-                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
-                .collect()
-        } else {
-            buffer_data
+    fn emit(&self, event: RecorderEvent) {
+        if let Some(ref handler) = self.shared.events {
+            handler(event);
+        }
+    }
+
+    /// Keep the lossless copy and warnings for the recorder and return the encoded audio.
+    fn hand_out(&self, result: Result<FinishedAudio, String>) -> Result<Vec<u8>, String> {
+        result.map(|audio| {
+            for warning in &audio.warnings {
+                self.warn(warning);
+            }
+            if let Ok(mut lossless) = self.shared.lossless.lock() {
+                *lossless = audio.lossless;
+            }
+            audio.encoded
+        })
+    }
+
+    /// Abort a session that could not start, reporting `error` to stop_recording.
+    fn fail(self, error: String) {
+        log::error!("{}", error);
+        println!(">>> ERROR: {}", error);
+        self.set_recording(false);
+
+        // Wait for the stop command so the caller sees the real reason
+        for command in self.commands.iter() {
+            match command {
+                CaptureCommand::Begin(_) | CaptureCommand::SetPaused(_) => {}
+                CaptureCommand::Stop(reply) => {
+                    let _ = reply.send(Err(error));
+                    break;
+                }
+                CaptureCommand::Shutdown => break,
+            }
+        }
+    }
+}
+
+// A recording in progress on the capture thread
+struct ActiveRecording {
+    pipeline: Option<CapturePipeline>,
+    // Source overruns when the recording started
+    start_stats: CaptureStats,
+    channel_mixes: HashMap<String, ChannelMix>,
+    trim_silence: bool,
+    max_duration_secs: u32,
+    // Audio arriving while paused is dropped
+    paused: bool,
+    // Hands-free only; `countdown` is the last remaining time announced
+    silence: Option<SilenceTimer>,
+    countdown: Option<u32>,
+    silence_expired: bool,
+    failure: Option<String>,
+}
+
+impl ActiveRecording {
+    fn new(source: &dyn AudioSource, options: &RecordingOptions) -> Self {
+        let pipeline_options = PipelineOptions {
+            channel_mix: options.channel_mix(source.name()),
+            ..options.pipeline.clone()
+        };
+        let pipeline = CapturePipeline::new(source.sample_rate(), source.channels(), &pipeline_options);
+        let (pipeline, failure) = match pipeline {
+            Ok(mut pipeline) => {
+                pipeline.set_tap(options.audio_tap.clone());
+                (Some(pipeline), None)
+            }
+            Err(e) => (None, Some(e)),
         };
+        Self {
+            pipeline,
+            start_stats: source.stats(),
+            channel_mixes: options.channel_mixes.clone(),
+            trim_silence: options.trim_silence,
+            max_duration_secs: options.max_duration_secs,
+            paused: false,
+            silence: options.silence_timeout_ms.map(SilenceTimer::new),
+            countdown: None,
+            silence_expired: false,
+            failure,
+        }
+    }
 
-        println!(">>> Mono data: {} samples", mono_data.len());
+    fn duration_secs(&self) -> f32 {
+        self.pipeline.as_ref().map_or(0.0, CapturePipeline::duration_secs)
+    }
 
-        // Resample to target sample rate if needed
-        let resampled = if source_rate != TARGET_SAMPLE_RATE {
-            println!(">>> Resampling from {} Hz to {} Hz", source_rate, TARGET_SAMPLE_RATE);
-            resample(&mono_data, source_rate, TARGET_SAMPLE_RATE)
+    /// Keep recording from `source`, which replaced a failed device.
+    fn switch_source(&mut self, source: &dyn AudioSource) {
+        if self.failure.is_some() {
+            return;
+        }
+        let channel_mix = self.channel_mixes.get(source.name()).copied().unwrap_or_default();
+        if let Some(Err(e)) = self
+            .pipeline
+            .as_mut()
+            .map(|p| p.switch_source(source.sample_rate(), source.channels(), channel_mix))
+        {
+            log::error!("Audio processing failed: {}", e);
+            self.failure = Some(e);
+        }
+    }
+
+    /// Check the duration and memory caps, and the hands-free silence timeout.
+    fn limit_reached(&self) -> Option<RecordingLimit> {
+        let pipeline = self.pipeline.as_ref()?;
+        let duration_secs = pipeline.duration_secs();
+        let encoded_bytes = pipeline.encoded_bytes();
+        let reason = if duration_secs >= self.max_duration_secs as f32 {
+            "duration"
+        } else if encoded_bytes >= MAX_ENCODED_BYTES {
+            "size"
+        } else if self.silence_expired {
+            "silence"
         } else {
-            mono_data
+            return None;
         };
+        Some(RecordingLimit { reason: reason.to_string(), duration_secs, encoded_bytes })
+    }
 
-        log::info!("Resampled to {} samples at {} Hz", resampled.len(), TARGET_SAMPLE_RATE);
-        println!(">>> Resampled to {} samples at {} Hz (duration: {:.2}s)",
-            resampled.len(),
-            TARGET_SAMPLE_RATE,
-            resampled.len() as f32 / TARGET_SAMPLE_RATE as f32
-        );
-
-        // Check minimum recording duration (at least 0.5 seconds = 8000 samples at 16kHz)
-        const MIN_SAMPLES: usize = 8000;
-        if resampled.len() < MIN_SAMPLES {
-            log::warn!(
-                "Recording too short: {} samples (minimum {}). Duration: {:.2}s",
-                resampled.len(),
-                MIN_SAMPLES,
-                resampled.len() as f32 / TARGET_SAMPLE_RATE as f32
-            );
-            println!(
-                ">>> WARNING: Recording too short: {} samples (minimum {}). Duration: {:.2}s",
-                resampled.len(),
-                MIN_SAMPLES,
-                resampled.len() as f32 / TARGET_SAMPLE_RATE as f32
-            );
-            return Err(format!(
-                "Recording too short ({:.1}s). Please hold the key longer.",
-                resampled.len() as f32 / TARGET_SAMPLE_RATE as f32
-            ));
-        }
-
-        // Convert to Opus/OGG format
-        println!(">>> Encoding to Opus/OGG...");
-        let result = samples_to_opus(&resampled);
-        if let Ok(ref data) = result {
-            println!(">>> Encoded successfully: {} bytes", data.len());
+    /// Hands-free: run `data` through the silence timer and return the
+    /// countdown to announce, if it changed. Once the timer expires,
+    /// `limit_reached` reports "silence".
+    fn track_silence(&mut self, data: &[f32], sample_rate: u32, channels: u16) -> Option<SilenceCountdown> {
+        let timer = self.silence.as_mut()?;
+        if data.is_empty() {
+            return None;
         }
-        result
+        let duration_ms = data.len() as f32 * 1000.0 / (sample_rate as f32 * channels.max(1) as f32);
+        let countdown = match timer.push(frame_energy_db(data), duration_ms) {
+            SilenceStatus::Expired => {
+                self.silence_expired = true;
+                return None;
+            }
+            SilenceStatus::CountingDown { remaining_ms } => {
+                Some(remaining_ms.div_ceil(COUNTDOWN_STEP_MS) * COUNTDOWN_STEP_MS)
+            }
+            SilenceStatus::Listening => None,
+        };
+        if countdown == self.countdown {
+            return None;
+        }
+        self.countdown = countdown;
+        Some(SilenceCountdown { remaining_ms: countdown, timeout_ms: timer.timeout_ms() })
     }
 
-    pub fn get_audio_level(&self) -> f32 {
-        let buffer = self.buffer.lock().unwrap();
-
-        // Get last 1000 samples or all if less
-        let samples_to_check = buffer.len().min(1000);
-        if samples_to_check == 0 {
-            return 0.0;
+    fn push(&mut self, data: &[f32]) {
+        if self.failure.is_some() || self.paused {
+            return;
+        }
+        if let Some(Err(e)) = self.pipeline.as_mut().map(|p| p.push(data)) {
+            log::error!("Audio processing failed: {}", e);
+            println!(">>> ERROR: Audio processing failed: {}", e);
+            self.failure = Some(e);
         }
+    }
 
-        let recent_samples = &buffer[buffer.len() - samples_to_check..];
+    fn finish(self) -> Result<FinishedAudio, String> {
+        match (self.failure, self.pipeline) {
+            (Some(e), _) => Err(e),
+            (None, Some(pipeline)) => pipeline.finish(self.trim_silence),
+            (None, None) => Err("No audio data captured. Microphone may not be working.".to_string()),
+        }
+    }
+}
 
-        // Calculate RMS (Root Mean Square)
-        let sum_squares: f32 = recent_samples.iter().map(|s| s * s).sum();
-        let rms = (sum_squares / samples_to_check as f32).sqrt();
+fn run_capture(context: CaptureContext) {
+    println!(">>> Recording thread started");
+    log::info!("Recording thread started");
 
-        // Normalize to 0.0 - 1.0 range
-        (rms * 10.0).min(1.0)
+    let mut source = match context.source.open(&context.channel_mixes) {
+        Ok(source) => source,
+        Err(e) => return context.fail(e),
+    };
+    if let Err(e) = source.start() {
+        return context.fail(e);
     }
-}
 
-fn build_stream_with_logging<T>(
-    device: &cpal::Device,
-    config: &cpal::StreamConfig,
-    buffer: Arc<StdMutex<Vec<f32>>>,
-    sample_counter: Arc<StdMutex<usize>>,
-    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-) -> Result<cpal::Stream, String>
-where
-    T: cpal::SizedSample,
-    f32: cpal::FromSample<T>,
-{
-    use std::time::Instant;
-    let start_time = Instant::now();
-    let last_log = Arc::new(StdMutex::new(Instant::now()));
-    let callback_count = Arc::new(StdMutex::new(0usize));
-
-    let stream = device
-        .build_input_stream(
-            config,
-            move |data: &[T], _: &cpal::InputCallbackInfo| {
-                if let Ok(mut buf) = buffer.lock() {
-                    let prev_len = buf.len();
-                    // Convert all samples to f32 (keep all channels, we'll convert to mono later)
-                    for sample in data {
-                        buf.push(f32::from_sample_(*sample));
+    log::info!("Input stream is playing (warm: {})", context.warm);
+    println!(">>> Input stream is playing (warm: {})", context.warm);
+
+    // Recent audio kept while idle so a new recording can start slightly in the past
+    let mut pre_roll = context
+        .warm
+        .then(|| PreRollBuffer::new(source.sample_rate(), source.channels(), context.pre_roll_ms));
+    let mut meter = LevelMeter::new(source.sample_rate(), source.channels());
+    let mut active: Option<ActiveRecording> = None;
+    // Result of a recording that was stopped early (limit or device failure), handed out at the next Stop
+    let mut finished: Option<Result<FinishedAudio, String>> = None;
+    let mut chunk: Vec<f32> = Vec::new();
+    let mut reconnect = context.reconnect;
+    // Set when the device failed and couldn't be replaced; the session ends at the next Stop
+    let mut source_lost = false;
+
+    loop {
+        match context.commands.recv_timeout(DRAIN_INTERVAL) {
+            Ok(CaptureCommand::Begin(options)) => {
+                if let Some(warning) = source.warning() {
+                    context.warn(warning);
+                }
+                finished = None;
+                reconnect = options.reconnect;
+                meter = LevelMeter::new(source.sample_rate(), source.channels());
+                let mut recording = ActiveRecording::new(source.as_ref(), &options);
+
+                // Audio captured before the hotkey press becomes the start of the recording
+                source.read(&mut chunk);
+                match pre_roll.as_mut() {
+                    Some(buffer) => {
+                        buffer.push(&chunk);
+                        let buffered = buffer.take();
+                        println!(">>> Prepending {} pre-roll samples", buffered.len());
+                        recording.push(&buffered);
                     }
-
-                    // Update sample counter
-                    if let Ok(mut count) = sample_counter.lock() {
-                        *count += data.len();
+                    None => recording.push(&chunk),
+                }
+                active = Some(recording);
+                log::info!("Recording started - stream is playing");
+                println!(">>> Recording started - stream is playing");
+            }
+            Ok(CaptureCommand::SetPaused(paused)) => {
+                let Some(recording) = active.as_mut().filter(|r| r.paused != paused) else {
+                    continue;
+                };
+                // Audio up to now belongs before the pause
+                source.read(&mut chunk);
+                recording.push(&chunk);
+                recording.paused = paused;
+                // The frontend drops the countdown while paused, announce it afresh after
+                recording.countdown = None;
+                meter = LevelMeter::new(source.sample_rate(), source.channels());
+                context.shared.level.store(0, Ordering::Relaxed);
+
+                let duration_secs = recording.duration_secs();
+                let action = if paused { "paused" } else { "resumed" };
+                log::info!("Recording {} at {:.1}s", action, duration_secs);
+                println!(">>> Recording {} at {:.1}s", action, duration_secs);
+                context.emit(RecorderEvent::Paused(PauseState { paused, duration_secs }));
+            }
+            Ok(CaptureCommand::Stop(reply)) => {
+                // Process whatever arrived since the last read
+                source.read(&mut chunk);
+                let result = match (active.take(), finished.take()) {
+                    (Some(mut recording), _) => {
+                        recording.push(&chunk);
+                        context.check_overflows(recording.start_stats, source.stats());
+                        recording.finish()
                     }
-
-                    // Update callback count
-                    if let Ok(mut count) = callback_count.lock() {
-                        *count += 1;
+                    (None, Some(result)) => result,
+                    (None, None) => Err("No audio data captured. Microphone may not be working.".to_string()),
+                };
+                context.set_recording(false);
+                let _ = reply.send(context.hand_out(result));
+
+                if !context.warm || source_lost {
+                    break;
+                }
+            }
+            Ok(CaptureCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {
+                if source_lost {
+                    continue;
+                }
+                source.read(&mut chunk);
+                context.update_stats(source.stats());
+
+                if let Some(message) = source.take_error() {
+                    log::error!("Input stream failed: {}", message);
+                    println!(">>> ERROR: Input stream failed: {}", message);
+                    // Keep what arrived before the failure
+                    if let Some(recording) = active.as_mut() {
+                        recording.push(&chunk);
                     }
+                    let duration_secs = active.as_ref().map_or(0.0, ActiveRecording::duration_secs);
+
+                    let fallback = match context.source {
+                        SourceKind::Microphone { .. } if reconnect => open_fallback_source(),
+                        _ => Err("Reconnecting is disabled".to_string()),
+                    };
+                    match fallback {
+                        Ok(replacement) => {
+                            source = replacement;
+                            let device = source.name().to_string();
+                            log::info!("Capture continues on {}", device);
+                            println!(">>> Capture continues on {}", device);
+
+                            meter = LevelMeter::new(source.sample_rate(), source.channels());
+                            if let Some(buffer) = pre_roll.as_mut() {
+                                *buffer = PreRollBuffer::new(source.sample_rate(), source.channels(), context.pre_roll_ms);
+                            }
+                            if let Some(recording) = active.as_mut() {
+                                recording.switch_source(source.as_ref());
+                                context.warn(&format!("{} - recording continued on '{}'", message, device));
+                                context.emit(RecorderEvent::StreamError(RecordingError {
+                                    message,
+                                    recovered: true,
+                                    device: Some(device),
+                                    duration_secs,
+                                }));
+                            }
+                        }
+                        Err(e) => {
+                            log::warn!("Could not reopen an input device: {}", e);
+                            println!(">>> WARNING: Could not reopen an input device: {}", e);
+                            let Some(recording) = active.take() else {
+                                // Idle warm session: nothing to keep, the next recording reopens the device
+                                break;
+                            };
+
+                            // Finish now; the audio is handed out when the frontend calls stop
+                            context.warn(&format!("{} - recording stopped early", message));
+                            finished = Some(recording.finish());
+                            source_lost = true;
+                            context.shared.level.store(0, Ordering::Relaxed);
+                            context.emit(RecorderEvent::StreamError(RecordingError {
+                                message,
+                                recovered: false,
+                                device: None,
+                                duration_secs,
+                            }));
+                        }
+                    }
+                    continue;
+                }
 
-                    // Log periodically (every 1 second)
-                    if let Ok(mut last) = last_log.lock() {
-                        if last.elapsed().as_secs() >= 1 {
-                            let elapsed = start_time.elapsed().as_secs_f32();
-                            let callbacks = callback_count.lock().map(|c| *c).unwrap_or(0);
+                if chunk.is_empty() {
+                    continue;
+                }
+                match (active.as_mut(), pre_roll.as_mut()) {
+                    (Some(recording), _) if recording.paused => {}
+                    (Some(recording), _) => {
+                        if let Some(levels) = meter.push(&chunk) {
+                            context.shared.level.store(levels.level.to_bits(), Ordering::Relaxed);
+                            context.emit(RecorderEvent::Level(levels));
+                        }
+                        recording.push(&chunk);
+                        if let Some(countdown) = recording.track_silence(&chunk, source.sample_rate(), source.channels()) {
+                            context.emit(RecorderEvent::SilenceCountdown(countdown));
+                        }
+
+                        if let Some(limit) = recording.limit_reached() {
+                            log::warn!(
+                                "Recording limit reached ({}): {:.1}s, {} bytes - stopping",
+                                limit.reason,
+                                limit.duration_secs,
+                                limit.encoded_bytes
+                            );
                             println!(
-                                ">>> Audio callback: +{} samples, total {} samples, {:.1}s elapsed, {} callbacks",
-                                data.len(),
-                                buf.len(),
-                                elapsed,
-                                callbacks
+                                ">>> WARNING: Recording limit reached ({}) after {:.1}s - stopping",
+                                limit.reason,
+                                limit.duration_secs
                             );
-                            *last = Instant::now();
+                            match limit.reason.as_str() {
+                                // The normal end of a hands-free recording, nothing to warn about
+                                "silence" => {}
+                                "duration" => context.warn(&format!(
+                                    "Recording stopped automatically after {:.0}s (maximum length reached)",
+                                    limit.duration_secs
+                                )),
+                                _ => context.warn("Recording stopped automatically (memory limit reached)"),
+                            }
+
+                            // Finish now; the audio is handed out when the frontend calls stop
+                            context.check_overflows(recording.start_stats, source.stats());
+                            finished = active.take().map(ActiveRecording::finish);
+                            context.shared.level.store(0, Ordering::Relaxed);
+                            context.emit(RecorderEvent::LimitReached(limit));
                         }
                     }
-
-                    // Log first callback
-                    if prev_len == 0 && !data.is_empty() {
-                        println!(">>> First audio callback received: {} samples", data.len());
-                    }
+                    (None, Some(buffer)) => buffer.push(&chunk),
+                    (None, None) => {}
                 }
-            },
-            err_fn,
-            None,
-        )
-        .map_err(|e| format!("Failed to build input stream: {}", e))?;
+            }
+        }
+    }
+
+    // Source is dropped here, closing the input stream
+    drop(source);
+
+    log::info!("Recording thread stopped");
+    println!(">>> Recording thread stopped");
+}
 
-    Ok(stream)
+/// Open the system default input device in place of one that failed.
+fn open_fallback_source() -> Result<Box<dyn AudioSource>, String> {
+    let mut last_error = String::new();
+    for attempt in 1..=RECONNECT_ATTEMPTS {
+        // Give the OS a moment to pick a new default device
+        thread::sleep(RECONNECT_DELAY);
+        let opened = SourceKind::default().open(&HashMap::new()).and_then(|mut source| {
+            source.start()?;
+            Ok(source)
+        });
+        match opened {
+            Ok(source) => return Ok(source),
+            Err(e) => {
+                println!(">>> Reconnect attempt {} failed: {}", attempt, e);
+                last_error = e;
+            }
+        }
+    }
+    Err(last_error)
 }
 
-/// Simple linear interpolation resampler
-fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-    if from_rate == to_rate {
-        return samples.to_vec();
+/// Record from `options.source` on the current thread, without hotkeys or a
+/// capture session, until a finite source runs out or the duration limit is
+/// reached. Used by headless runs, where there may be no audio device at all.
+/// Fails if the encoded audio outgrows the in-memory cap first.
+pub fn record_source(options: &RecordingOptions) -> Result<Vec<u8>, String> {
+    let mut source = options.source.open(&options.channel_mixes)?;
+    source.start()?;
+    log::info!(
+        "Recording from {} ({} Hz, {} channels)",
+        source.name(),
+        source.sample_rate(),
+        source.channels()
+    );
+    if let Some(warning) = source.warning() {
+        log::warn!("{}", warning);
     }
 
-    let ratio = from_rate as f64 / to_rate as f64;
-    let output_len = (samples.len() as f64 / ratio) as usize;
-    let mut output = Vec::with_capacity(output_len);
+    let mut recording = ActiveRecording::new(source.as_ref(), options);
+    let mut chunk: Vec<f32> = Vec::new();
 
-    for i in 0..output_len {
-        let src_idx = i as f64 * ratio;
-        let idx_floor = src_idx.floor() as usize;
-        let idx_ceil = (idx_floor + 1).min(samples.len() - 1);
-        let frac = src_idx - idx_floor as f64;
+    loop {
+        source.read(&mut chunk);
+        recording.push(&chunk);
 
-        let sample = samples[idx_floor] * (1.0 - frac as f32) + samples[idx_ceil] * frac as f32;
-        output.push(sample);
+        if source.is_exhausted() {
+            break;
+        }
+        if let Some(error) = source.take_error() {
+            log::warn!("{} - finishing with the audio captured so far", error);
+            break;
+        }
+        if let Some(limit) = recording.limit_reached() {
+            // Stopping at the memory cap would pass off a truncated source as the whole thing
+            if limit.reason == "size" {
+                return Err(format!(
+                    "Audio is too long: it passed the {} MB limit after {:.0} minutes",
+                    MAX_ENCODED_BYTES / (1024 * 1024),
+                    limit.duration_secs / 60.0
+                ));
+            }
+            log::warn!("Recording limit reached ({}) after {:.1}s", limit.reason, limit.duration_secs);
+            break;
+        }
+        if chunk.is_empty() {
+            thread::sleep(DRAIN_INTERVAL);
+        }
     }
 
-    output
+    recording.finish().map(|audio| {
+        for warning in &audio.warnings {
+            log::warn!("{}", warning);
+        }
+        audio.encoded
+    })
 }
 
-fn samples_to_opus(samples: &[f32]) -> Result<Vec<u8>, String> {
-    println!(">>> samples_to_opus: input {} f32 samples", samples.len());
-
-    // Create Opus encoder
-    let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
-        .map_err(|e| format!("Failed to create Opus encoder: {:?}", e))?;
-
-    // Set bitrate for voice (16kbps is good for speech)
-    encoder.set_bitrate(audiopus::Bitrate::BitsPerSecond(16000))
-        .map_err(|e| format!("Failed to set bitrate: {:?}", e))?;
-
-    // Convert f32 samples to i16
-    let i16_samples: Vec<i16> = samples
-        .iter()
-        .map(|s| (*s * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
-        .collect();
-
-    println!(">>> samples_to_opus: converted to {} i16 samples", i16_samples.len());
-
-    // Check if samples are all zeros (silence)
-    let non_zero_count = i16_samples.iter().filter(|&&s| s != 0).count();
-    let max_sample = i16_samples.iter().map(|s| s.abs()).max().unwrap_or(0);
-    println!(">>> samples_to_opus: non-zero samples: {}, max amplitude: {}", non_zero_count, max_sample);
-
-    // Create OGG container
-    let mut cursor = Cursor::new(Vec::new());
-    let serial = rand_serial();
-    let mut packet_writer = ogg::writing::PacketWriter::new(&mut cursor);
-
-    // Write Opus header (OpusHead)
-    let opus_head = create_opus_head();
-    packet_writer.write_packet(opus_head, serial, PacketWriteEndInfo::EndPage, 0)
-        .map_err(|e| format!("Failed to write OpusHead: {}", e))?;
-
-    // Write Opus comment header (OpusTags)
-    let opus_tags = create_opus_tags();
-    packet_writer.write_packet(opus_tags, serial, PacketWriteEndInfo::EndPage, 0)
-        .map_err(|e| format!("Failed to write OpusTags: {}", e))?;
-
-    // Encode audio in frames
-    let mut granule_pos: u64 = 0;
-    let mut encoded_buf = vec![0u8; 4000]; // Max Opus packet size
-    let mut frame_count = 0;
-    let mut total_encoded_bytes = 0;
-    let total_frames = (i16_samples.len() + OPUS_FRAME_SIZE - 1) / OPUS_FRAME_SIZE;
-
-    println!(">>> samples_to_opus: will encode {} frames (OPUS_FRAME_SIZE={})", total_frames, OPUS_FRAME_SIZE);
-
-    for (idx, chunk) in i16_samples.chunks(OPUS_FRAME_SIZE).enumerate() {
-        // Pad last frame if needed
-        let frame: Vec<i16> = if chunk.len() < OPUS_FRAME_SIZE {
-            let mut padded = chunk.to_vec();
-            padded.resize(OPUS_FRAME_SIZE, 0);
-            padded
-        } else {
-            chunk.to_vec()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    use crate::audio::decode::decode_bytes;
+    use crate::audio::encoder::encoded_duration_secs;
+    use crate::audio::source::{Signal, SyntheticSource};
+    use crate::transcription::{Transcript, TranscriptionOptions, TranscriptionProvider};
+
+    const PRE_ROLL_MS: u32 = 50;
+    const SOURCE_MS: u32 = 80;
+
+    // 80 ms of stereo 48 kHz tone: 3840 frames, so a single read (4096 frames
+    // for a non-realtime source) delivers all of it, whenever that read happens
+    fn options(warm_microphone: bool) -> RecordingOptions {
+        RecordingOptions {
+            source: SourceKind::Synthetic {
+                signal: Signal::Sine { frequency_hz: 440.0, amplitude: 0.3 },
+                sample_rate: 48000,
+                channels: 2,
+                duration_ms: SOURCE_MS,
+                realtime: false,
+            },
+            warm_microphone,
+            pre_roll_ms: PRE_ROLL_MS,
+            max_duration_secs: 60,
+            pipeline: PipelineOptions {
+                encoder: EncoderSettings { format: UploadFormat::Wav, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn assert_duration_ms(encoded: &[u8], expected_ms: u32) {
+        let duration = encoded_duration_secs(encoded).unwrap();
+        // Allow for rounding to whole 16 kHz samples
+        assert!((duration * 1000.0 - expected_ms as f64).abs() < 1.0, "recorded {} s", duration);
+    }
+
+    #[test]
+    fn warm_microphone_prepends_pre_roll() {
+        let options = options(true);
+        let mut recorder = AudioRecorder::new_dummy();
+        recorder.apply_warm_microphone(&options);
+
+        // Whether the idle session or the start of the recording reads the
+        // source, all of it arrived before the recording began, and only the
+        // last PRE_ROLL_MS of that is kept
+        recorder.start_recording(options).unwrap();
+        let encoded = recorder.stop_recording().unwrap();
+        assert_duration_ms(&encoded, PRE_ROLL_MS);
+    }
+
+    #[test]
+    fn cold_microphone_has_no_pre_roll() {
+        let mut recorder = AudioRecorder::new_dummy();
+        recorder.start_recording(options(false)).unwrap();
+
+        // The source opens with the recording, so all of it is recorded and none is cut to the pre-roll
+        let encoded = recorder.stop_recording().unwrap();
+        assert_duration_ms(&encoded, SOURCE_MS);
+    }
+
+    fn tone_source(duration_ms: u32) -> SourceKind {
+        SourceKind::Synthetic {
+            signal: Signal::Sine { frequency_hz: 440.0, amplitude: 0.3 },
+            sample_rate: 44100,
+            channels: 2,
+            duration_ms,
+            realtime: false,
+        }
+    }
+
+    #[test]
+    fn record_source_encodes_synthetic_tone() {
+        let options = RecordingOptions {
+            source: tone_source(1500),
+            max_duration_secs: 60,
+            ..Default::default()
         };
+        let encoded = record_source(&options).unwrap();
 
-        let encoded_len = encoder.encode(&frame, &mut encoded_buf)
-            .map_err(|e| format!("Failed to encode Opus frame: {:?}", e))?;
+        let decoded = decode_bytes(encoded, None).unwrap();
+        assert_eq!(decoded.channels, 1);
+        assert!((decoded.duration_secs() - 1.5).abs() < 0.001, "decoded {} s", decoded.duration_secs());
+    }
 
-        frame_count += 1;
-        total_encoded_bytes += encoded_len;
+    #[test]
+    fn record_source_stops_at_max_duration() {
+        let options = RecordingOptions {
+            source: tone_source(5000),
+            max_duration_secs: 2,
+            ..Default::default()
+        };
+        let encoded = record_source(&options).unwrap();
 
-        // Granule position is in 48kHz samples (Opus standard), so multiply by 3 (48000/16000)
-        granule_pos += (OPUS_FRAME_SIZE as u64) * 3;
+        let duration = decode_bytes(encoded, None).unwrap().duration_secs();
+        // The limit is checked after each read of up to 4096 frames
+        assert!((2.0..2.1).contains(&duration), "decoded {} s", duration);
+    }
 
-        let is_last = idx == total_frames - 1;
-        let end_info = if is_last {
-            PacketWriteEndInfo::EndStream
-        } else {
-            PacketWriteEndInfo::NormalPacket
+    #[test]
+    fn record_source_refuses_audio_past_the_size_cap() {
+        // 40 minutes of 16 kHz PCM is about 77 MB, over the cap before the source runs out
+        let options = RecordingOptions {
+            source: SourceKind::Synthetic {
+                signal: Signal::Silence,
+                sample_rate: 16000,
+                channels: 1,
+                duration_ms: 40 * 60 * 1000,
+                realtime: false,
+            },
+            trim_silence: false,
+            max_duration_secs: u32::MAX,
+            pipeline: PipelineOptions {
+                encoder: EncoderSettings { format: UploadFormat::Wav, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
         };
+        let error = record_source(&options).unwrap_err();
+        assert!(error.starts_with("Audio is too long"), "{}", error);
+    }
+
+    /// Stands in for a speech engine: reports the duration of the audio it was sent.
+    struct DecodingProvider;
 
-        packet_writer.write_packet(
-            encoded_buf[..encoded_len].to_vec(),
-            serial,
-            end_info,
-            granule_pos,
-        ).map_err(|e| format!("Failed to write Opus packet: {}", e))?;
+    #[async_trait]
+    impl TranscriptionProvider for DecodingProvider {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        async fn transcribe(&self, audio: Vec<u8>, _options: &TranscriptionOptions) -> Result<Transcript, String> {
+            let decoded = decode_bytes(audio, None)?;
+            Ok(Transcript {
+                text: "tone".to_string(),
+                duration_secs: Some(decoded.duration_secs() as f64),
+                ..Default::default()
+            })
+        }
     }
 
-    println!(">>> samples_to_opus: encoded {} frames, {} bytes of audio data", frame_count, total_encoded_bytes);
+    #[tokio::test]
+    async fn synthetic_recording_reaches_the_provider() {
+        let options = RecordingOptions {
+            source: tone_source(1000),
+            trim_silence: true,
+            max_duration_secs: 60,
+            ..Default::default()
+        };
+        let encoded = tokio::task::spawn_blocking(move || record_source(&options)).await.unwrap().unwrap();
+
+        let provider: Box<dyn TranscriptionProvider> = Box::new(DecodingProvider);
+        let transcript = provider.transcribe(encoded, &TranscriptionOptions::default()).await.unwrap();
+        assert_eq!(transcript.text, "tone");
+        // A tone is speech as far as the VAD can tell, so nothing is trimmed
+        let duration = transcript.duration_secs.unwrap();
+        assert!((duration - 1.0).abs() < 0.001, "provider got {} s", duration);
+    }
 
-    let result = cursor.into_inner();
-    log::info!("Encoded {} samples to {} bytes Opus/OGG", samples.len(), result.len());
-    println!(">>> samples_to_opus: final OGG file size: {} bytes", result.len());
+    /// Feed `source` to `recording` as the capture loop does, until a limit stops it.
+    fn run_hands_free(recording: &mut ActiveRecording, mut source: SyntheticSource) -> Option<RecordingLimit> {
+        let mut chunk = Vec::new();
+        source.start().unwrap();
+        while !source.is_exhausted() {
+            source.read(&mut chunk);
+            recording.push(&chunk);
+            recording.track_silence(&chunk, source.sample_rate(), source.channels());
+            if let Some(limit) = recording.limit_reached() {
+                return Some(limit);
+            }
+        }
+        None
+    }
 
-    Ok(result)
-}
+    fn hands_free_options(max_duration_secs: u32) -> RecordingOptions {
+        RecordingOptions {
+            max_duration_secs,
+            silence_timeout_ms: Some(1000),
+            pipeline: PipelineOptions {
+                encoder: EncoderSettings { format: UploadFormat::Wav, ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
 
-fn rand_serial() -> u32 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos() as u32)
-        .unwrap_or(12345678)
-}
+    fn speech(duration_ms: u32) -> SyntheticSource {
+        SyntheticSource::new(Signal::Sine { frequency_hz: 300.0, amplitude: 0.3 }, 16000, 1, duration_ms, false)
+    }
 
-fn create_opus_head() -> Vec<u8> {
-    let mut head = Vec::with_capacity(19);
-    head.extend_from_slice(b"OpusHead");  // Magic signature
-    head.push(1);                          // Version
-    head.push(1);                          // Channel count (mono)
-    head.extend_from_slice(&0u16.to_le_bytes());  // Pre-skip
-    head.extend_from_slice(&48000u32.to_le_bytes()); // Original sample rate (Opus always uses 48kHz internally)
-    head.extend_from_slice(&0i16.to_le_bytes());  // Output gain
-    head.push(0);                          // Channel mapping family
-    head
-}
+    #[test]
+    fn hands_free_stops_after_trailing_silence() {
+        let speech = speech(1000);
+        let mut recording = ActiveRecording::new(&speech, &hands_free_options(60));
+        assert!(run_hands_free(&mut recording, speech).is_none());
+
+        let silence = SyntheticSource::new(Signal::Silence, 16000, 1, 5000, false);
+        let limit = run_hands_free(&mut recording, silence).unwrap();
+        assert_eq!(limit.reason, "silence");
+        // One second of speech, then the timeout rounded up to whole reads
+        assert!((2.0..2.3).contains(&limit.duration_secs), "stopped after {} s", limit.duration_secs);
+    }
 
-fn create_opus_tags() -> Vec<u8> {
-    let mut tags = Vec::new();
-    tags.extend_from_slice(b"OpusTags");  // Magic signature
-    let vendor = b"FluxVoice";
-    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
-    tags.extend_from_slice(vendor);
-    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
-    tags
+    #[test]
+    fn hands_free_stops_at_max_duration_while_talking() {
+        let speech = speech(10_000);
+        let mut recording = ActiveRecording::new(&speech, &hands_free_options(3));
+        let limit = run_hands_free(&mut recording, speech).unwrap();
+        assert_eq!(limit.reason, "duration");
+        assert!((3.0..3.3).contains(&limit.duration_secs), "stopped after {} s", limit.duration_secs);
+    }
+
+    #[test]
+    fn hands_free_caps_the_recording_length() {
+        let mut config = AppConfig::default();
+        config.audio.max_recording_secs = 300;
+        config.audio.hands_free_max_secs = 60;
+        config.audio.silence_timeout_ms = 2000;
+        assert_eq!(RecordingOptions::from(&config).silence_timeout_ms, None);
+        assert_eq!(RecordingOptions::from(&config).max_duration_secs, 300);
+
+        config.audio.hands_free = true;
+        let options = RecordingOptions::from(&config);
+        assert_eq!(options.silence_timeout_ms, Some(2000));
+        assert_eq!(options.max_duration_secs, 60);
+
+        // The overall limit still applies if it is shorter
+        config.audio.max_recording_secs = 30;
+        assert_eq!(RecordingOptions::from(&config).max_duration_secs, 30);
+    }
 }
@@ -0,0 +1,288 @@
+use std::f64::consts::PI;
+
+// Sinc zero crossings on each side of the filter centre (higher = sharper cutoff)
+const ZERO_CROSSINGS: u64 = 16;
+// Place the cutoff slightly below Nyquist so the transition band is attenuated
+const ROLLOFF: f64 = 0.9;
+// Kaiser window shape, roughly 85 dB of stopband attenuation
+const KAISER_BETA: f64 = 8.6;
+// Only compact the history buffer once this many samples are stale
+const HISTORY_COMPACT_THRESHOLD: usize = 8192;
+
+/// Band-limited rational resampler (polyphase windowed-sinc FIR).
+///
+/// The rate change is reduced to `up / down`, and a Kaiser-windowed sinc
+/// low-pass filter is evaluated only at the phases that produce output
+/// samples. It can be fed in arbitrary chunks, so it works both on whole
+/// recordings and on a live stream.
+pub struct Resampler {
+    up: u64,
+    down: u64,
+    taps: usize,
+    // Phase-major table, taps stored in reverse order for a straight dot product
+    coeffs: Vec<f32>,
+    delay: u64,
+    history: Vec<f32>,
+    // Absolute input index of history[0] (negative while primed with zeros)
+    history_start: i64,
+    input_count: u64,
+    output_count: u64,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        let g = gcd(from_rate as u64, to_rate as u64).max(1);
+        let up = to_rate as u64 / g;
+        let down = from_rate as u64 / g;
+
+        if up == down {
+            return Self {
+                up: 1,
+                down: 1,
+                taps: 0,
+                coeffs: Vec::new(),
+                delay: 0,
+                history: Vec::new(),
+                history_start: 0,
+                input_count: 0,
+                output_count: 0,
+            };
+        }
+
+        let factor = up.max(down);
+        let taps = (2 * ZERO_CROSSINGS * factor).div_ceil(up) as usize;
+        let len = taps * up as usize;
+        let center = (len - 1) as f64 / 2.0;
+        let cutoff = ROLLOFF * 0.5 / factor as f64;
+        let half_width = len as f64 / 2.0;
+        let i0_beta = bessel_i0(KAISER_BETA);
+
+        let prototype: Vec<f64> = (0..len)
+            .map(|j| {
+                let x = j as f64 - center;
+                let sinc = if x == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    (2.0 * PI * cutoff * x).sin() / (PI * x)
+                };
+                let r = (x / half_width).clamp(-1.0, 1.0);
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).sqrt()) / i0_beta;
+                // Scale by `up` to make up for the energy lost to zero-stuffing
+                sinc * window * up as f64
+            })
+            .collect();
+
+        let mut coeffs = vec![0.0f32; len];
+        for phase in 0..up as usize {
+            for k in 0..taps {
+                coeffs[phase * taps + (taps - 1 - k)] = prototype[phase + k * up as usize] as f32;
+            }
+        }
+
+        Self {
+            up,
+            down,
+            taps,
+            coeffs,
+            delay: (len as u64 - 1) / 2,
+            history: vec![0.0; taps],
+            history_start: -(taps as i64),
+            input_count: 0,
+            output_count: 0,
+        }
+    }
+
+    fn is_passthrough(&self) -> bool {
+        self.taps == 0
+    }
+
+    /// Resample `input`, appending whatever output is available to `output`.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        self.history.extend_from_slice(input);
+        self.input_count += input.len() as u64;
+        self.drain(output, u64::MAX);
+    }
+
+    /// Emit the remaining output, treating everything after the last input as silence.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            return;
+        }
+
+        let expected = (self.input_count * self.up).div_ceil(self.down);
+        if self.output_count < expected {
+            let needed = self.input_index(expected - 1) as i64 + 1;
+            let available = self.history_start + self.history.len() as i64;
+            if needed > available {
+                self.history.extend(std::iter::repeat_n(0.0, (needed - available) as usize));
+            }
+            self.drain(output, expected);
+        }
+    }
+
+    fn input_index(&self, n: u64) -> u64 {
+        (n * self.down + self.delay) / self.up
+    }
+
+    /// Produce output samples up to `limit` while the input history covers them.
+    fn drain(&mut self, output: &mut Vec<f32>, limit: u64) {
+        let available = (self.history_start + self.history.len() as i64) as u64;
+        while self.output_count < limit && self.input_index(self.output_count) < available {
+            output.push(self.compute(self.output_count));
+            self.output_count += 1;
+        }
+        self.compact();
+    }
+
+    fn compute(&self, n: u64) -> f32 {
+        let t = n * self.down + self.delay;
+        let i = (t / self.up) as i64;
+        let phase = (t % self.up) as usize;
+
+        let end = (i - self.history_start) as usize + 1;
+        let window = &self.history[end - self.taps..end];
+        let coeffs = &self.coeffs[phase * self.taps..(phase + 1) * self.taps];
+
+        coeffs.iter().zip(window).map(|(c, x)| c * x).sum()
+    }
+
+    fn compact(&mut self) {
+        let oldest_needed = self.input_index(self.output_count) as i64 - self.taps as i64 + 1;
+        let stale = (oldest_needed - self.history_start).max(0) as usize;
+        if stale >= HISTORY_COMPACT_THRESHOLD {
+            self.history.drain(..stale);
+            self.history_start += stale as i64;
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Zeroth-order modified Bessel function of the first kind (power series).
+fn bessel_i0(x: f64) -> f64 {
+    let half_x = x / 2.0;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for k in 1..64 {
+        term *= (half_x / k as f64).powi(2);
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(freq: f64, rate: u32, samples: usize, amplitude: f64) -> Vec<f32> {
+        (0..samples)
+            .map(|i| (amplitude * (2.0 * PI * freq * i as f64 / rate as f64).sin()) as f32)
+            .collect()
+    }
+
+    fn resample(input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut resampler = Resampler::new(48000, 16000);
+        let mut output = Vec::new();
+        for block in input.chunks(chunk) {
+            resampler.process(block, &mut output);
+        }
+        resampler.flush(&mut output);
+        output
+    }
+
+    /// Amplitude of the `freq` component (single-bin DFT).
+    fn amplitude_at(samples: &[f32], freq: f64, rate: u32) -> f64 {
+        let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+            let w = 2.0 * PI * freq * i as f64 / rate as f64;
+            (re + x as f64 * w.cos(), im - x as f64 * w.sin())
+        });
+        2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+    }
+
+    fn rms(samples: &[f32]) -> f64 {
+        (samples.iter().map(|&x| (x as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+    }
+
+    // Skip the filter's ramp-up and ramp-down at either end
+    fn steady(samples: &[f32]) -> &[f32] {
+        &samples[200..samples.len() - 200]
+    }
+
+    #[test]
+    fn suppresses_tones_above_nyquist() {
+        for freq in [10_000.0, 11_000.0, 12_000.0] {
+            let input = tone(freq, 48000, 48000, 0.5);
+            let output = resample(&input, 4800);
+            let attenuation_db = 20.0 * (rms(steady(&output)) / rms(&input)).log10();
+            assert!(attenuation_db < -60.0, "{} Hz only attenuated by {:.1} dB", freq, attenuation_db);
+        }
+    }
+
+    #[test]
+    fn passband_tone_keeps_amplitude_and_frequency() {
+        let input = tone(1000.0, 48000, 48000, 0.5);
+        let output = resample(&input, 4800);
+        let steady = &output[400..output.len() - 400]; // 15200 samples, a whole number of cycles
+        let amplitude = amplitude_at(steady, 1000.0, 16000);
+        assert!((amplitude - 0.5).abs() < 0.005, "1 kHz amplitude {}", amplitude);
+        // Nearly all the energy is at 1 kHz
+        let rms_at_tone = amplitude / 2f64.sqrt();
+        assert!((rms(steady) - rms_at_tone).abs() < 0.005);
+        assert!(amplitude_at(steady, 1100.0, 16000) < 0.01);
+    }
+
+    #[test]
+    fn output_length_is_a_third_of_input() {
+        for len in [48000, 48001, 48002, 1000] {
+            let input = tone(440.0, 48000, len, 0.5);
+            let one_shot = resample(&input, len);
+            assert_eq!(one_shot.len(), len.div_ceil(3));
+        }
+    }
+
+    #[test]
+    fn chunked_feeding_matches_one_shot() {
+        let input = tone(440.0, 48000, 48001, 0.5);
+        let one_shot = resample(&input, input.len());
+
+        let mut resampler = Resampler::new(48000, 16000);
+        let mut chunked = Vec::new();
+        let mut rest = input.as_slice();
+        let mut size = 1;
+        while !rest.is_empty() {
+            let (block, tail) = rest.split_at(size.min(rest.len()));
+            resampler.process(block, &mut chunked);
+            rest = tail;
+            size = size * 7 % 1013 + 1;
+        }
+        resampler.flush(&mut chunked);
+
+        assert_eq!(chunked.len(), one_shot.len());
+        assert!(chunked.iter().zip(&one_shot).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn equal_rates_pass_through() {
+        let input = tone(440.0, 16000, 1000, 0.5);
+        let mut resampler = Resampler::new(16000, 16000);
+        let mut output = Vec::new();
+        resampler.process(&input, &mut output);
+        resampler.flush(&mut output);
+        assert_eq!(output, input);
+    }
+}
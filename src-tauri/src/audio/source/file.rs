@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use super::{AudioSource, Pacer};
+use crate::audio::decode::decode_file;
+
+/// Plays back an audio file (WAV, FLAC, MP3 or OGG) as if it were being recorded.
+pub struct FileSource {
+    name: String,
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<f32>,
+    position: usize,
+    pacer: Pacer,
+}
+
+impl FileSource {
+    pub fn open(path: &Path, realtime: bool) -> Result<Self, String> {
+        let decoded = decode_file(path)?;
+
+        Ok(Self {
+            name: path.display().to_string(),
+            sample_rate: decoded.sample_rate,
+            channels: decoded.channels,
+            samples: decoded.samples,
+            position: 0,
+            pacer: Pacer::new(decoded.sample_rate, realtime),
+        })
+    }
+}
+
+impl AudioSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        self.pacer.start();
+        Ok(())
+    }
+
+    fn read(&mut self, chunk: &mut Vec<f32>) {
+        chunk.clear();
+        let channels = self.channels.max(1) as usize;
+        let remaining = ((self.samples.len() - self.position) / channels) as u64;
+        let count = self.pacer.frames_due(remaining) as usize * channels;
+        chunk.extend_from_slice(&self.samples[self.position..self.position + count]);
+        self.position += count;
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.samples.len() - self.position < self.channels.max(1) as usize
+    }
+}
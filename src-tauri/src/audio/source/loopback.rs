@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+use super::{AudioSource, CaptureStats, MicrophoneSource};
+
+// WASAPI loopback delivers nothing while no application plays audio. Once no
+// data arrived for this long the gap is filled with silence, so the recording
+// keeps its real length and stays in step with the microphone.
+const GAP_TOLERANCE: Duration = Duration::from_millis(200);
+
+/// Capture of what the system plays: a monitor source on Linux, a loopback
+/// driver on macOS, or an output device in WASAPI loopback mode on Windows.
+pub struct LoopbackSource {
+    inner: MicrophoneSource,
+    started: Option<Instant>,
+    last_data: Instant,
+    delivered_frames: u64,
+}
+
+impl LoopbackSource {
+    /// Open `device_name`, or the default loopback device when it is `None`
+    /// or can no longer be found.
+    pub fn open(device_name: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            inner: MicrophoneSource::open_loopback(device_name)?,
+            started: None,
+            last_data: Instant::now(),
+            delivered_frames: 0,
+        })
+    }
+}
+
+impl AudioSource for LoopbackSource {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn warning(&self) -> Option<&str> {
+        self.inner.warning()
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        self.inner.start()?;
+        self.started = Some(Instant::now());
+        self.last_data = Instant::now();
+        self.delivered_frames = 0;
+        Ok(())
+    }
+
+    fn read(&mut self, chunk: &mut Vec<f32>) {
+        self.inner.read(chunk);
+        let channels = self.channels() as u64;
+        if !chunk.is_empty() {
+            self.last_data = Instant::now();
+            self.delivered_frames += chunk.len() as u64 / channels;
+            return;
+        }
+
+        let Some(started) = self.started else {
+            return;
+        };
+        if self.last_data.elapsed() < GAP_TOLERANCE {
+            return;
+        }
+        // Catch up to real time, less the tolerance so audio resuming right now isn't delayed
+        let behind = started.elapsed().saturating_sub(GAP_TOLERANCE);
+        let expected = (behind.as_secs_f64() * self.sample_rate() as f64) as u64;
+        if expected > self.delivered_frames {
+            let missing = expected - self.delivered_frames;
+            chunk.resize((missing * channels) as usize, 0.0);
+            self.delivered_frames = expected;
+        }
+    }
+
+    fn stats(&self) -> CaptureStats {
+        self.inner.stats()
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.inner.take_error()
+    }
+}
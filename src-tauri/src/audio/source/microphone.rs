@@ -0,0 +1,302 @@
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::{HeapCons, HeapProd, HeapRb};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+
+use super::{AudioSource, CaptureStats};
+use crate::audio::devices::{open_input_device, open_loopback_device};
+
+// Some backends stop calling back without reporting an error when a device goes away
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+// Audio the ring buffer holds before the callback has to drop samples. The
+// capture thread drains it every few ms, but may pause while reopening a device.
+const RING_BUFFER_SECS: usize = 4;
+// How often read() logs the callback statistics
+const STATS_LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+// Written by the audio callback with plain atomics, read by the capture thread
+#[derive(Default)]
+struct CallbackCounters {
+    callbacks: AtomicU64,
+    samples: AtomicU64,
+    overflows: AtomicU64,
+    dropped_samples: AtomicU64,
+}
+
+/// Live capture from an input device through cpal, or from a loopback device
+/// (see `LoopbackSource`).
+pub struct MicrophoneSource {
+    name: String,
+    sample_rate: u32,
+    channels: u16,
+    warning: Option<String>,
+    stream: cpal::Stream,
+    // Filled by the audio callback without locking, drained on every read
+    consumer: HeapCons<f32>,
+    counters: Arc<CallbackCounters>,
+    started: Instant,
+    last_log: Instant,
+    logged_samples: u64,
+    // Stream errors reported by cpal: (fatal, message)
+    stream_error: Arc<StdMutex<Option<(bool, String)>>>,
+    last_data: Instant,
+    failed: bool,
+    // Loopback streams legitimately go quiet while nothing plays, so they never stall
+    loopback: bool,
+}
+
+impl MicrophoneSource {
+    /// Open `device_name`, or the system default input device when it is
+    /// `None` or can no longer be found.
+    pub fn open(device_name: Option<&str>) -> Result<Self, String> {
+        let host = cpal::default_host();
+        println!(">>> Audio host: {:?}", host.id());
+
+        let (device, warning) = open_input_device(&host, device_name)?;
+        Self::open_device(device, warning, false)
+    }
+
+    /// Open `device_name` to capture what the system plays, see `open_loopback_device`.
+    pub fn open_loopback(device_name: Option<&str>) -> Result<Self, String> {
+        let host = cpal::default_host();
+        println!(">>> Audio host: {:?}", host.id());
+
+        let (device, warning) = open_loopback_device(&host, device_name)?;
+        Self::open_device(device, warning, true)
+    }
+
+    fn open_device(device: cpal::Device, warning: Option<String>, loopback: bool) -> Result<Self, String> {
+        if let Some(ref warning) = warning {
+            println!(">>> WARN: {}", warning);
+        }
+
+        let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        let role = if loopback { "loopback" } else { "input" };
+        log::info!("Using audio {} device: {}", role, name);
+        println!(">>> Using audio {} device: {}", role, name);
+
+        // WASAPI captures an output device in loopback mode when an input stream is
+        // built on it, in the output's format
+        let supported_config = if loopback && cfg!(target_os = "windows") {
+            device.default_output_config()
+        } else {
+            device.default_input_config()
+        }
+        .map_err(|e| format!("Failed to get default input config: {}", e))?;
+
+        // Use the device's native config (don't override sample rate/channels)
+        let config = supported_config.config();
+
+        log::info!(
+            "Audio config: {} Hz, {} channels, format: {:?}",
+            config.sample_rate.0,
+            config.channels,
+            supported_config.sample_format()
+        );
+        println!(
+            ">>> Audio config: {} Hz, {} channels, format: {:?}",
+            config.sample_rate.0,
+            config.channels,
+            supported_config.sample_format()
+        );
+
+        let stream_error = Arc::new(StdMutex::new(None));
+        let err_fn = {
+            let stream_error = Arc::clone(&stream_error);
+            move |err: cpal::StreamError| {
+                log::error!("Stream error: {}", err);
+                println!(">>> Stream error: {}", err);
+                // Backend-specific errors are often recoverable (e.g. an overrun),
+                // keep them only to explain a stall
+                let fatal = matches!(err, cpal::StreamError::DeviceNotAvailable);
+                if let Ok(mut slot) = stream_error.lock() {
+                    if !slot.as_ref().is_some_and(|(was_fatal, _)| *was_fatal) {
+                        *slot = Some((fatal, err.to_string()));
+                    }
+                }
+            }
+        };
+
+        let capacity = config.sample_rate.0 as usize * config.channels as usize * RING_BUFFER_SECS;
+        let (producer, consumer) = HeapRb::<f32>::new(capacity).split();
+        let counters = Arc::new(CallbackCounters::default());
+
+        let stream = match supported_config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, producer, counters.clone(), err_fn),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, producer, counters.clone(), err_fn),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, producer, counters.clone(), err_fn),
+            sample_format => {
+                return Err(format!("Unsupported sample format: {}", sample_format));
+            }
+        }
+        .map_err(|e| format!("Failed to build stream: {}", e))?;
+
+        Ok(Self {
+            name,
+            sample_rate: config.sample_rate.0,
+            channels: config.channels,
+            warning,
+            stream,
+            consumer,
+            counters,
+            started: Instant::now(),
+            last_log: Instant::now(),
+            logged_samples: 0,
+            stream_error,
+            last_data: Instant::now(),
+            failed: false,
+            loopback,
+        })
+    }
+}
+
+impl AudioSource for MicrophoneSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        self.started = Instant::now();
+        self.last_log = Instant::now();
+        self.last_data = Instant::now();
+        self.stream
+            .play()
+            .map_err(|e| format!("Failed to play stream: {}", e))
+    }
+
+    fn read(&mut self, chunk: &mut Vec<f32>) {
+        chunk.clear();
+        chunk.extend(self.consumer.pop_iter());
+        if !chunk.is_empty() {
+            self.last_data = Instant::now();
+        }
+        self.log_callback_stats();
+    }
+
+    fn stats(&self) -> CaptureStats {
+        CaptureStats {
+            overflows: self.counters.overflows.load(Ordering::Relaxed),
+            dropped_samples: self.counters.dropped_samples.load(Ordering::Relaxed),
+        }
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        if self.failed {
+            return None;
+        }
+
+        let kind = if self.loopback { "System audio device" } else { "Microphone" };
+        let stalled = !self.loopback && self.last_data.elapsed() >= STALL_TIMEOUT;
+        let reported = self.stream_error.lock().ok().and_then(|mut slot| slot.take());
+        let error = match reported {
+            Some((true, _)) => Some(format!("{} '{}' was disconnected", kind, self.name)),
+            Some((false, message)) if stalled => {
+                Some(format!("{} '{}' stopped working: {}", kind, self.name, message))
+            }
+            Some((false, message)) => {
+                // Not fatal yet, keep it in case the stream stalls
+                if let Ok(mut slot) = self.stream_error.lock() {
+                    slot.get_or_insert((false, message));
+                }
+                None
+            }
+            None if stalled => Some(format!("{} '{}' stopped delivering audio", kind, self.name)),
+            None => None,
+        };
+
+        self.failed = error.is_some();
+        error
+    }
+}
+
+impl MicrophoneSource {
+    // Logging happens here on the capture thread, never in the audio callback
+    fn log_callback_stats(&mut self) {
+        let total = self.counters.samples.load(Ordering::Relaxed);
+        if self.logged_samples == 0 && total > 0 {
+            println!(">>> First audio callback received: {} samples", total);
+            self.logged_samples = total;
+        }
+
+        if self.last_log.elapsed() >= STATS_LOG_INTERVAL {
+            println!(
+                ">>> Audio callback: +{} samples, total {} samples, {:.1}s elapsed, {} callbacks",
+                total - self.logged_samples,
+                total,
+                self.started.elapsed().as_secs_f32(),
+                self.counters.callbacks.load(Ordering::Relaxed)
+            );
+            self.logged_samples = total;
+            self.last_log = Instant::now();
+        }
+    }
+}
+
+impl Drop for MicrophoneSource {
+    fn drop(&mut self) {
+        // Log final sample count
+        let count = self.counters.samples.load(Ordering::Relaxed);
+        let stats = self.stats();
+        println!(
+            ">>> Closing input stream - total samples collected in callbacks: {} ({} overflows, {} samples dropped)",
+            count, stats.overflows, stats.dropped_samples
+        );
+        log::info!(
+            "Closing input stream - total samples collected in callbacks: {} ({} overflows, {} samples dropped)",
+            count,
+            stats.overflows,
+            stats.dropped_samples
+        );
+    }
+}
+
+/// Build an input stream whose callback is real-time safe: it converts the
+/// samples into the ring buffer and bumps atomic counters, nothing else.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut producer: HeapProd<f32>,
+    counters: Arc<CallbackCounters>,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    let stream = device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                // Keep all channels, the pipeline converts to mono
+                let pushed = producer.push_iter(data.iter().map(|s| f32::from_sample_(*s)));
+
+                counters.callbacks.fetch_add(1, Ordering::Relaxed);
+                counters.samples.fetch_add(pushed as u64, Ordering::Relaxed);
+                if pushed < data.len() {
+                    // The capture thread fell behind, the rest of this buffer is lost
+                    counters.overflows.fetch_add(1, Ordering::Relaxed);
+                    counters.dropped_samples.fetch_add((data.len() - pushed) as u64, Ordering::Relaxed);
+                }
+            },
+            err_fn,
+            None,
+        )
+        .map_err(|e| format!("Failed to build input stream: {}", e))?;
+
+    Ok(stream)
+}
@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+
+use super::{AudioSource, CaptureStats};
+use crate::audio::downmix::{ChannelMix, Downmixer};
+use crate::audio::resample::Resampler;
+
+// Loopback audio buffered before it is mixed in, so the jitter between the two
+// devices' callbacks doesn't leave holes in the mix
+const MIX_LATENCY_MS: usize = 60;
+// Loopback audio queued beyond this is dropped, bounding its delay against the microphone
+const MAX_MIX_LAG_MS: usize = 500;
+
+/// The microphone and system audio summed into one mono stream.
+///
+/// The microphone is the clock: each of its samples gets whatever loopback
+/// audio is queued, resampled to the microphone's rate, or silence if none is.
+pub struct MixedSource {
+    name: String,
+    warning: Option<String>,
+    microphone: Box<dyn AudioSource>,
+    loopback: Box<dyn AudioSource>,
+    microphone_mix: Downmixer,
+    loopback_mix: Downmixer,
+    resampler: Resampler,
+    // Mono loopback audio at the microphone's rate, waiting to be mixed
+    queue: VecDeque<f32>,
+    primed: bool,
+    buffer: Vec<f32>,
+    mono: Vec<f32>,
+    resampled: Vec<f32>,
+}
+
+impl MixedSource {
+    /// `microphone_mix` is the microphone's channel selection; system audio is always averaged.
+    pub fn new(microphone: Box<dyn AudioSource>, loopback: Box<dyn AudioSource>, microphone_mix: ChannelMix) -> Self {
+        let rate = microphone.sample_rate();
+        let name = format!("{} + {}", microphone.name(), loopback.name());
+        let warnings: Vec<&str> = [microphone.warning(), loopback.warning()].into_iter().flatten().collect();
+        println!(">>> Mixing system audio from {} into {}", loopback.name(), microphone.name());
+
+        Self {
+            name,
+            warning: (!warnings.is_empty()).then(|| warnings.join("; ")),
+            microphone_mix: Downmixer::new(microphone_mix, microphone.channels(), rate),
+            loopback_mix: Downmixer::new(ChannelMix::Average, loopback.channels(), loopback.sample_rate()),
+            resampler: Resampler::new(loopback.sample_rate(), rate),
+            microphone,
+            loopback,
+            queue: VecDeque::new(),
+            primed: false,
+            buffer: Vec::new(),
+            mono: Vec::new(),
+            resampled: Vec::new(),
+        }
+    }
+
+    fn ms_to_samples(&self, ms: usize) -> usize {
+        self.microphone.sample_rate() as usize * ms / 1000
+    }
+}
+
+impl AudioSource for MixedSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.microphone.sample_rate()
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        self.microphone.start()?;
+        self.loopback.start()
+    }
+
+    fn read(&mut self, chunk: &mut Vec<f32>) {
+        chunk.clear();
+
+        // Queue the loopback audio as mono at the microphone's rate
+        self.loopback.read(&mut self.buffer);
+        self.mono.clear();
+        self.loopback_mix.process(&self.buffer, &mut self.mono);
+        self.resampled.clear();
+        self.resampler.process(&self.mono, &mut self.resampled);
+        self.queue.extend(self.resampled.iter());
+
+        let max_lag = self.ms_to_samples(MAX_MIX_LAG_MS);
+        if self.queue.len() > max_lag {
+            self.queue.drain(..self.queue.len() - max_lag);
+        }
+        if !self.primed && self.queue.len() >= self.ms_to_samples(MIX_LATENCY_MS) {
+            self.primed = true;
+        }
+
+        self.microphone.read(&mut self.buffer);
+        self.mono.clear();
+        self.microphone_mix.process(&self.buffer, &mut self.mono);
+        for &sample in &self.mono {
+            let system = match self.primed.then(|| self.queue.pop_front()).flatten() {
+                Some(system) => system,
+                None => {
+                    // Ran dry, wait for the latency to build up again
+                    self.primed = false;
+                    0.0
+                }
+            };
+            chunk.push((sample + system).clamp(-1.0, 1.0));
+        }
+    }
+
+    fn stats(&self) -> CaptureStats {
+        let (a, b) = (self.microphone.stats(), self.loopback.stats());
+        CaptureStats {
+            overflows: a.overflows + b.overflows,
+            dropped_samples: a.dropped_samples + b.dropped_samples,
+        }
+    }
+
+    fn take_error(&mut self) -> Option<String> {
+        self.microphone.take_error().or_else(|| self.loopback.take_error())
+    }
+}
@@ -0,0 +1,163 @@
+pub mod file;
+pub mod loopback;
+pub mod microphone;
+pub mod mixed;
+pub mod synthetic;
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+
+pub use file::FileSource;
+pub use loopback::LoopbackSource;
+pub use microphone::MicrophoneSource;
+pub use mixed::MixedSource;
+pub use synthetic::{Signal, SyntheticSource};
+
+use super::downmix::ChannelMix;
+
+/// Buffer overruns of a live source, for diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CaptureStats {
+    /// Times the capture thread fell behind and the audio callback had to drop samples.
+    pub overflows: u64,
+    pub dropped_samples: u64,
+}
+
+/// Somewhere interleaved f32 audio comes from.
+///
+/// Sources are pulled: the capture loop calls `read` every few milliseconds
+/// and feeds whatever arrived into the pipeline. A source is created and
+/// used on the capture thread only, so it doesn't need to be `Send`.
+pub trait AudioSource {
+    fn name(&self) -> &str;
+    fn sample_rate(&self) -> u32;
+    fn channels(&self) -> u16;
+
+    /// Warning to show with each recording (e.g. a fallback device was used).
+    fn warning(&self) -> Option<&str> {
+        None
+    }
+
+    /// Begin producing audio.
+    fn start(&mut self) -> Result<(), String>;
+
+    /// Replace the contents of `chunk` with the samples that arrived since the last call.
+    fn read(&mut self, chunk: &mut Vec<f32>);
+
+    /// True once a finite source has delivered all of its audio.
+    fn is_exhausted(&self) -> bool {
+        false
+    }
+
+    /// Overruns since the source was opened.
+    fn stats(&self) -> CaptureStats {
+        CaptureStats::default()
+    }
+
+    /// A fatal error since the last call (e.g. the device was unplugged).
+    /// The source delivers no more audio after reporting one.
+    fn take_error(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// Which source to record from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceKind {
+    /// Input device by name, falling back to the system default.
+    Microphone { device_name: Option<String> },
+    /// What the system plays, from a loopback device by name or the default one.
+    Loopback { device_name: Option<String> },
+    /// Microphone and system audio mixed, e.g. to transcribe both sides of a call.
+    Mixed { microphone: Option<String>, loopback: Option<String> },
+    /// An audio file. Without `realtime` it is delivered as fast as it can be processed.
+    File { path: PathBuf, realtime: bool },
+    /// A generated signal of a fixed length.
+    Synthetic {
+        signal: Signal,
+        sample_rate: u32,
+        channels: u16,
+        duration_ms: u32,
+        realtime: bool,
+    },
+}
+
+impl Default for SourceKind {
+    fn default() -> Self {
+        SourceKind::Microphone { device_name: None }
+    }
+}
+
+impl SourceKind {
+    /// Short tag stored with each transcription to tell where its audio came from.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SourceKind::Microphone { .. } => "microphone",
+            SourceKind::Loopback { .. } => "system",
+            SourceKind::Mixed { .. } => "microphone+system",
+            SourceKind::File { .. } => "file",
+            SourceKind::Synthetic { .. } => "synthetic",
+        }
+    }
+
+    /// Open the source. `channel_mixes` (per device name) only matters where the
+    /// source downmixes a device itself, i.e. the microphone of `Mixed`.
+    pub fn open(&self, channel_mixes: &HashMap<String, ChannelMix>) -> Result<Box<dyn AudioSource>, String> {
+        Ok(match self {
+            SourceKind::Microphone { device_name } => {
+                Box::new(MicrophoneSource::open(device_name.as_deref())?)
+            }
+            SourceKind::Loopback { device_name } => Box::new(LoopbackSource::open(device_name.as_deref())?),
+            SourceKind::Mixed { microphone, loopback } => {
+                let microphone: Box<dyn AudioSource> = Box::new(MicrophoneSource::open(microphone.as_deref())?);
+                let loopback: Box<dyn AudioSource> = Box::new(LoopbackSource::open(loopback.as_deref())?);
+                let microphone_mix = channel_mixes.get(microphone.name()).copied().unwrap_or_default();
+                Box::new(MixedSource::new(microphone, loopback, microphone_mix))
+            }
+            SourceKind::File { path, realtime } => Box::new(FileSource::open(path, *realtime)?),
+            SourceKind::Synthetic { signal, sample_rate, channels, duration_ms, realtime } => {
+                Box::new(SyntheticSource::new(*signal, *sample_rate, *channels, *duration_ms, *realtime))
+            }
+        })
+    }
+}
+
+// Frames handed out per read when a finite source isn't paced in real time
+const FAST_READ_FRAMES: u64 = 4096;
+
+/// Decides how many frames a finite source should hand out on each read.
+struct Pacer {
+    sample_rate: u32,
+    realtime: bool,
+    started: Option<Instant>,
+    delivered: u64,
+}
+
+impl Pacer {
+    fn new(sample_rate: u32, realtime: bool) -> Self {
+        Self { sample_rate, realtime, started: None, delivered: 0 }
+    }
+
+    fn start(&mut self) {
+        self.started = Some(Instant::now());
+        self.delivered = 0;
+    }
+
+    /// Number of frames to deliver now, at most `remaining`.
+    fn frames_due(&mut self, remaining: u64) -> u64 {
+        let Some(started) = self.started else {
+            return 0;
+        };
+        let due = if self.realtime {
+            let elapsed = started.elapsed().as_secs_f64();
+            ((elapsed * self.sample_rate as f64) as u64).saturating_sub(self.delivered)
+        } else {
+            FAST_READ_FRAMES
+        };
+        let due = due.min(remaining);
+        self.delivered += due;
+        due
+    }
+}
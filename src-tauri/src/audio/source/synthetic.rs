@@ -0,0 +1,86 @@
+use std::f64::consts::PI;
+
+use super::{AudioSource, Pacer};
+
+/// Generated test signal, written to every channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Silence,
+    Sine { frequency_hz: f32, amplitude: f32 },
+    WhiteNoise { amplitude: f32 },
+}
+
+/// Produces a fixed length of a generated signal, for tests and headless runs.
+pub struct SyntheticSource {
+    signal: Signal,
+    sample_rate: u32,
+    channels: u16,
+    total_frames: u64,
+    frame: u64,
+    noise_state: u32,
+    pacer: Pacer,
+}
+
+impl SyntheticSource {
+    pub fn new(signal: Signal, sample_rate: u32, channels: u16, duration_ms: u32, realtime: bool) -> Self {
+        Self {
+            signal,
+            sample_rate,
+            channels: channels.max(1),
+            total_frames: sample_rate as u64 * duration_ms as u64 / 1000,
+            frame: 0,
+            noise_state: 0x2545_f491,
+            pacer: Pacer::new(sample_rate, realtime),
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        match self.signal {
+            Signal::Silence => 0.0,
+            Signal::Sine { frequency_hz, amplitude } => {
+                let t = self.frame as f64 / self.sample_rate as f64;
+                amplitude * (2.0 * PI * frequency_hz as f64 * t).sin() as f32
+            }
+            Signal::WhiteNoise { amplitude } => {
+                // xorshift32, deterministic so runs are repeatable
+                self.noise_state ^= self.noise_state << 13;
+                self.noise_state ^= self.noise_state >> 17;
+                self.noise_state ^= self.noise_state << 5;
+                amplitude * (self.noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+            }
+        }
+    }
+}
+
+impl AudioSource for SyntheticSource {
+    fn name(&self) -> &str {
+        "Synthetic signal"
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn start(&mut self) -> Result<(), String> {
+        self.pacer.start();
+        Ok(())
+    }
+
+    fn read(&mut self, chunk: &mut Vec<f32>) {
+        chunk.clear();
+        let frames = self.pacer.frames_due(self.total_frames - self.frame);
+        for _ in 0..frames {
+            let sample = self.next_sample();
+            chunk.extend(std::iter::repeat_n(sample, self.channels as usize));
+            self.frame += 1;
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.frame >= self.total_frames
+    }
+}
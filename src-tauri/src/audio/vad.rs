@@ -0,0 +1,294 @@
+/// Error returned when a recording contains no detectable speech.
+/// The frontend matches on this string, so keep it stable.
+pub const NO_SPEECH_DETECTED: &str = "No speech detected";
+
+const SILENCE_FLOOR_DB: f32 = -100.0;
+// Speech must rise this far above the estimated noise floor
+const SPEECH_MARGIN_DB: f32 = 10.0;
+// Never treat anything quieter than this as speech, even in a silent room
+const MIN_SPEECH_THRESHOLD_DB: f32 = -50.0;
+// Anything louder than this is speech, even if the floor estimate is high
+const MAX_SPEECH_THRESHOLD_DB: f32 = -35.0;
+// Minimum run of loud frames to count as speech (filters clicks and bumps)
+const MIN_SPEECH_MS: u32 = 90;
+// Audio kept before the first and after the last speech frame
+const PADDING_MS: u32 = 300;
+
+/// RMS level of a frame in dBFS.
+pub fn frame_energy_db(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return SILENCE_FLOOR_DB;
+    }
+    let sum_squares: f32 = frame.iter().map(|s| s * s).sum();
+    let rms = (sum_squares / frame.len() as f32).sqrt();
+    if rms <= 0.0 {
+        SILENCE_FLOOR_DB
+    } else {
+        (20.0 * rms.log10()).max(SILENCE_FLOOR_DB)
+    }
+}
+
+/// Find the speech region in a sequence of per-frame energies (dBFS).
+/// Returns `[start, end)` frame indices, or `None` if no frame run qualifies as speech.
+pub fn find_speech(energies: &[f32], frame_ms: u32) -> Option<(usize, usize)> {
+    if energies.is_empty() {
+        return None;
+    }
+
+    // Estimate the noise floor from the quietest 10% of frames
+    let noise_floor = percentile(energies, 10);
+    let threshold = (noise_floor + SPEECH_MARGIN_DB)
+        .clamp(MIN_SPEECH_THRESHOLD_DB, MAX_SPEECH_THRESHOLD_DB);
+
+    let min_run = MIN_SPEECH_MS.div_ceil(frame_ms.max(1)).max(1) as usize;
+
+    let mut first: Option<usize> = None;
+    let mut last: Option<usize> = None;
+    let mut run_start = 0;
+    let mut run_len = 0;
+
+    for (i, &energy) in energies.iter().enumerate() {
+        if energy >= threshold {
+            if run_len == 0 {
+                run_start = i;
+            }
+            run_len += 1;
+            if run_len >= min_run {
+                first.get_or_insert(run_start);
+                last = Some(i);
+            }
+        } else {
+            run_len = 0;
+        }
+    }
+
+    log::info!(
+        "VAD: noise floor {:.1} dBFS, threshold {:.1} dBFS, speech frames {:?}..{:?} of {}",
+        noise_floor,
+        threshold,
+        first,
+        last,
+        energies.len()
+    );
+
+    match (first, last) {
+        (Some(start), Some(end)) => Some((start, end + 1)),
+        _ => None,
+    }
+}
+
+/// Rough SNR estimate from per-frame energies: the level of the loudest 10%
+/// of frames (speech) over that of the quietest 10% (noise floor).
+pub fn estimate_snr_db(energies: &[f32]) -> Option<f32> {
+    if energies.len() < 10 {
+        return None;
+    }
+    Some(percentile(energies, 90) - percentile(energies, 10))
+}
+
+/// Typical level of the loudest (speech) frames: the 90th percentile energy.
+pub fn speech_level_db(energies: &[f32]) -> Option<f32> {
+    if energies.len() < 10 {
+        return None;
+    }
+    Some(percentile(energies, 90))
+}
+
+fn percentile(energies: &[f32], percent: usize) -> f32 {
+    let mut sorted = energies.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    sorted[(sorted.len() * percent / 100).min(sorted.len() - 1)]
+}
+
+/// Number of frames to keep on either side of the detected speech.
+pub fn padding_frames(frame_ms: u32) -> usize {
+    PADDING_MS.div_ceil(frame_ms.max(1)) as usize
+}
+
+// Hands-free recordings with no speech at all end after this long
+const NO_SPEECH_TIMEOUT_MS: f32 = 10_000.0;
+// Silence shorter than this is a pause between words and isn't announced
+const COUNTDOWN_AFTER_MS: f32 = 500.0;
+// The noise floor estimate drops to quieter input at once but rises at most this fast
+const FLOOR_RISE_DB_PER_SEC: f32 = 3.0;
+
+/// What `SilenceTimer` concluded from the audio so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SilenceStatus {
+    /// Speech, or not enough silence to announce yet.
+    Listening,
+    /// Silence after speech, the recording stops in `remaining_ms`.
+    CountingDown { remaining_ms: u32 },
+    /// Silent for the whole timeout.
+    Expired,
+}
+
+/// Ends a hands-free recording after a stretch of trailing silence. It uses
+/// the same thresholds as `find_speech`, but tracks the noise floor as the
+/// audio streams in.
+pub struct SilenceTimer {
+    timeout_ms: f32,
+    noise_floor: Option<f32>,
+    heard_speech: bool,
+    speech_run_ms: f32,
+    silent_ms: f32,
+}
+
+impl SilenceTimer {
+    pub fn new(timeout_ms: u32) -> Self {
+        Self {
+            timeout_ms: timeout_ms as f32,
+            noise_floor: None,
+            heard_speech: false,
+            speech_run_ms: 0.0,
+            silent_ms: 0.0,
+        }
+    }
+
+    pub fn timeout_ms(&self) -> u32 {
+        self.timeout_ms as u32
+    }
+
+    /// Account for `duration_ms` of audio with the given energy (dBFS).
+    pub fn push(&mut self, energy_db: f32, duration_ms: f32) -> SilenceStatus {
+        let floor = match self.noise_floor {
+            Some(floor) if energy_db > floor => floor + (energy_db - floor).min(FLOOR_RISE_DB_PER_SEC * duration_ms / 1000.0),
+            _ => energy_db,
+        };
+        self.noise_floor = Some(floor);
+        let threshold = (floor + SPEECH_MARGIN_DB).clamp(MIN_SPEECH_THRESHOLD_DB, MAX_SPEECH_THRESHOLD_DB);
+
+        if energy_db >= threshold {
+            self.speech_run_ms += duration_ms;
+        } else {
+            self.speech_run_ms = 0.0;
+        }
+        if self.speech_run_ms >= MIN_SPEECH_MS as f32 {
+            self.heard_speech = true;
+            self.silent_ms = 0.0;
+            return SilenceStatus::Listening;
+        }
+        self.silent_ms += duration_ms;
+
+        let timeout_ms = if self.heard_speech { self.timeout_ms } else { NO_SPEECH_TIMEOUT_MS.max(self.timeout_ms) };
+        if self.silent_ms >= timeout_ms {
+            SilenceStatus::Expired
+        } else if self.heard_speech && self.silent_ms >= COUNTDOWN_AFTER_MS.min(timeout_ms / 2.0) {
+            SilenceStatus::CountingDown { remaining_ms: (timeout_ms - self.silent_ms) as u32 }
+        } else {
+            SilenceStatus::Listening
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: u32 = 20;
+    const NOISE_DB: f32 = -70.0;
+    const SPEECH_DB: f32 = -20.0;
+
+    fn energies(runs: &[(f32, usize)]) -> Vec<f32> {
+        runs.iter().flat_map(|&(db, frames)| std::iter::repeat_n(db, frames)).collect()
+    }
+
+    #[test]
+    fn frame_energy_of_full_scale_square_is_zero_dbfs() {
+        assert_eq!(frame_energy_db(&[1.0, -1.0, 1.0, -1.0]), 0.0);
+        assert_eq!(frame_energy_db(&[0.0; 320]), SILENCE_FLOOR_DB);
+        assert_eq!(frame_energy_db(&[]), SILENCE_FLOOR_DB);
+    }
+
+    #[test]
+    fn trims_leading_and_trailing_silence() {
+        let frames = energies(&[(NOISE_DB, 50), (SPEECH_DB, 30), (NOISE_DB, 40)]);
+        assert_eq!(find_speech(&frames, FRAME_MS), Some((50, 80)));
+    }
+
+    #[test]
+    fn keeps_pauses_between_speech() {
+        let frames = energies(&[(NOISE_DB, 20), (SPEECH_DB, 10), (NOISE_DB, 25), (SPEECH_DB, 10), (NOISE_DB, 20)]);
+        assert_eq!(find_speech(&frames, FRAME_MS), Some((20, 65)));
+    }
+
+    #[test]
+    fn ignores_clicks_shorter_than_min_speech() {
+        // 60 ms of loud frames is below the 90 ms minimum
+        let frames = energies(&[(NOISE_DB, 20), (SPEECH_DB, 3), (NOISE_DB, 20), (SPEECH_DB, 10), (NOISE_DB, 20)]);
+        assert_eq!(find_speech(&frames, FRAME_MS), Some((43, 53)));
+    }
+
+    #[test]
+    fn silence_has_no_speech() {
+        assert_eq!(find_speech(&[], FRAME_MS), None);
+        assert_eq!(find_speech(&energies(&[(SILENCE_FLOOR_DB, 100)]), FRAME_MS), None);
+        assert_eq!(find_speech(&energies(&[(NOISE_DB, 100)]), FRAME_MS), None);
+        // Steady room noise just below the absolute speech threshold
+        assert_eq!(find_speech(&energies(&[(-55.0, 100)]), FRAME_MS), None);
+    }
+
+    #[test]
+    fn padding_rounds_up_to_whole_frames() {
+        assert_eq!(padding_frames(20), 15);
+        assert_eq!(padding_frames(60), 5);
+        assert_eq!(padding_frames(40), 8);
+        assert_eq!(padding_frames(7), 43);
+        assert_eq!(padding_frames(0), PADDING_MS as usize);
+    }
+
+    /// Push `ms` of audio at `db` in `FRAME_MS` steps, returning every status.
+    fn feed(timer: &mut SilenceTimer, db: f32, ms: u32) -> Vec<SilenceStatus> {
+        (0..ms / FRAME_MS).map(|_| timer.push(db, FRAME_MS as f32)).collect()
+    }
+
+    #[test]
+    fn silence_timer_waits_for_speech() {
+        let mut timer = SilenceTimer::new(1500);
+        // Silence before anyone spoke doesn't count down
+        let statuses = feed(&mut timer, NOISE_DB, 5000);
+        assert!(statuses.iter().all(|&s| s == SilenceStatus::Listening));
+
+        // ...but ends the recording after the no-speech timeout
+        let statuses = feed(&mut timer, NOISE_DB, NO_SPEECH_TIMEOUT_MS as u32 - 5000);
+        assert_eq!(statuses.last(), Some(&SilenceStatus::Expired));
+        assert!(statuses[..statuses.len() - 1].iter().all(|&s| s == SilenceStatus::Listening));
+    }
+
+    #[test]
+    fn silence_timer_counts_down_after_speech() {
+        let mut timer = SilenceTimer::new(1500);
+        feed(&mut timer, SPEECH_DB, 500);
+        let statuses = feed(&mut timer, NOISE_DB, 1500);
+
+        // A short pause between words is not announced
+        let announced = statuses.iter().position(|s| matches!(s, SilenceStatus::CountingDown { .. })).unwrap();
+        assert_eq!((announced as u32 + 1) * FRAME_MS, COUNTDOWN_AFTER_MS as u32);
+        assert_eq!(statuses[announced], SilenceStatus::CountingDown { remaining_ms: 1000 });
+        assert_eq!(statuses[statuses.len() - 2], SilenceStatus::CountingDown { remaining_ms: FRAME_MS });
+        assert_eq!(statuses.last(), Some(&SilenceStatus::Expired));
+    }
+
+    #[test]
+    fn silence_timer_resets_when_speech_resumes() {
+        let mut timer = SilenceTimer::new(1500);
+        feed(&mut timer, SPEECH_DB, 500);
+        assert!(matches!(feed(&mut timer, NOISE_DB, 1000).last(), Some(SilenceStatus::CountingDown { .. })));
+
+        assert_eq!(feed(&mut timer, SPEECH_DB, 200).last(), Some(&SilenceStatus::Listening));
+        // The full timeout applies again
+        let statuses = feed(&mut timer, NOISE_DB, 1500);
+        assert!(!statuses[..statuses.len() - 1].contains(&SilenceStatus::Expired));
+        assert_eq!(statuses.last(), Some(&SilenceStatus::Expired));
+    }
+
+    #[test]
+    fn silence_timer_ignores_clicks() {
+        let mut timer = SilenceTimer::new(1500);
+        feed(&mut timer, NOISE_DB, 1000);
+        // 60 ms is too short to count as speech
+        feed(&mut timer, SPEECH_DB, 60);
+        let statuses = feed(&mut timer, NOISE_DB, 2000);
+        assert!(statuses.iter().all(|&s| s == SilenceStatus::Listening));
+    }
+}
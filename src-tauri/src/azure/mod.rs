@@ -1,4 +1,6 @@
 pub mod speech;
+pub mod speech_stand_in;
+pub mod speech_stream;
 pub mod openai;
 
 use std::sync::OnceLock;
@@ -1,9 +1,17 @@
 use serde::{Deserialize, Serialize};
 use reqwest::multipart;
 use super::get_http_client;
+use crate::audio::encoder::{encoded_duration_secs, UploadFormat};
+use crate::transcription::{with_retry, Transcript, TranscriptSegment, TranscriptWord};
+
+// Fast Transcription rejects files above these limits
+const MAX_UPLOAD_BYTES: usize = 300 * 1024 * 1024;
+const MAX_AUDIO_DURATION_SECS: f64 = 2.0 * 60.0 * 60.0;
 
 #[derive(Debug, Deserialize)]
 struct FastTranscriptionResponse {
+    #[serde(rename = "durationMilliseconds")]
+    duration_milliseconds: Option<u64>,
     #[serde(rename = "combinedPhrases")]
     combined_phrases: Option<Vec<CombinedPhrase>>,
     phrases: Option<Vec<Phrase>>,
@@ -15,10 +23,49 @@ struct CombinedPhrase {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct Phrase {
     text: Option<String>,
-    #[allow(dead_code)]
     locale: Option<String>,
+    #[serde(default)]
+    offset_milliseconds: u64,
+    #[serde(default)]
+    duration_milliseconds: u64,
+    confidence: Option<f32>,
+    #[serde(default)]
+    words: Vec<Word>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Word {
+    text: String,
+    #[serde(default)]
+    offset_milliseconds: u64,
+    #[serde(default)]
+    duration_milliseconds: u64,
+    confidence: Option<f32>,
+}
+
+impl Phrase {
+    fn to_segment(&self) -> Option<TranscriptSegment> {
+        Some(TranscriptSegment {
+            text: self.text.clone().filter(|text| !text.is_empty())?,
+            offset_ms: self.offset_milliseconds,
+            duration_ms: self.duration_milliseconds,
+            confidence: self.confidence,
+            words: self
+                .words
+                .iter()
+                .map(|word| TranscriptWord {
+                    text: word.text.clone(),
+                    offset_ms: word.offset_milliseconds,
+                    duration_ms: word.duration_milliseconds,
+                    confidence: word.confidence,
+                })
+                .collect(),
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -32,7 +79,9 @@ pub async fn transcribe_audio(
     region: &str,
     languages: &[String],  // Changed to support multiple languages
     multilingual: bool,     // When true, send empty locales for multi-lingual model
-) -> Result<String, String> {
+) -> Result<Transcript, String> {
+    let format = UploadFormat::detect(&audio_data);
+
     // Use Fast Transcription API with multi-language support
     let url = format!(
         "https://{}.api.cognitive.microsoft.com/speechtotext/transcriptions:transcribe?api-version=2025-10-15",
@@ -43,11 +92,11 @@ pub async fn transcribe_audio(
 
     // In multilingual mode, send empty locales to let the API auto-detect
     let locales = if multilingual {
-        log::info!("Sending {} bytes of Opus audio to Azure Fast Transcription API (multilingual mode)", audio_data.len());
+        log::info!("Sending {} bytes of {:?} audio to Azure Fast Transcription API (multilingual mode)", audio_data.len(), format);
         println!(">>> Transcribing in multilingual mode");
         vec![]
     } else {
-        log::info!("Sending {} bytes of Opus audio to Azure Fast Transcription API (languages: {:?})", audio_data.len(), languages);
+        log::info!("Sending {} bytes of {:?} audio to Azure Fast Transcription API (languages: {:?})", audio_data.len(), format, languages);
         println!(">>> Transcribing with languages: {:?}", languages);
         languages.to_vec()
     };
@@ -60,10 +109,10 @@ pub async fn transcribe_audio(
     let definition_json = serde_json::to_string(&definition)
         .map_err(|e| format!("Failed to serialize definition: {}", e))?;
 
-    // Create multipart form with Opus/OGG audio
+    // Create multipart form with the Opus/OGG or WAV audio
     let audio_part = multipart::Part::bytes(audio_data)
-        .file_name("audio.ogg")
-        .mime_str("audio/ogg")
+        .file_name(format.file_name())
+        .mime_str(format.mime_type())
         .map_err(|e| format!("Failed to create audio part: {}", e))?;
 
     let definition_part = multipart::Part::text(definition_json)
@@ -94,20 +143,38 @@ pub async fn transcribe_audio(
         .await
         .map_err(|e| format!("Parse error: {}", e))?;
 
+    // The locale of the first phrase stands for the whole recording
+    let language = result
+        .phrases
+        .as_ref()
+        .and_then(|phrases| phrases.iter().find_map(|p| p.locale.clone()));
+    let segments: Vec<TranscriptSegment> = result
+        .phrases
+        .iter()
+        .flatten()
+        .filter_map(Phrase::to_segment)
+        .collect();
+    let transcript = |text: String| Transcript {
+        text,
+        language: language.clone(),
+        duration_secs: result.duration_milliseconds.map(|ms| ms as f64 / 1000.0),
+        segments: segments.clone(),
+    };
+
     // Extract text from combinedPhrases (preferred) or phrases
-    if let Some(combined) = result.combined_phrases {
+    if let Some(combined) = &result.combined_phrases {
         if let Some(first) = combined.first() {
             if let Some(text) = &first.text {
                 if !text.is_empty() {
                     log::info!("Transcription successful");
-                    return Ok(text.clone());
+                    return Ok(transcript(text.clone()));
                 }
             }
         }
     }
 
     // Fallback to concatenating phrases
-    if let Some(phrases) = result.phrases {
+    if let Some(phrases) = &result.phrases {
         let text: String = phrases
             .iter()
             .filter_map(|p| p.text.as_ref())
@@ -117,13 +184,36 @@ pub async fn transcribe_audio(
 
         if !text.is_empty() {
             log::info!("Transcription successful (from phrases)");
-            return Ok(text);
+            return Ok(transcript(text));
         }
     }
 
     Err("No transcription text in response".to_string())
 }
 
+/// Refuse audio that Fast Transcription would reject, before uploading it.
+fn check_upload_limits(audio_data: &[u8]) -> Result<(), String> {
+    if audio_data.len() > MAX_UPLOAD_BYTES {
+        return Err(format!(
+            "Recording is too large to transcribe ({:.1} MB, limit is {} MB)",
+            audio_data.len() as f64 / (1024.0 * 1024.0),
+            MAX_UPLOAD_BYTES / (1024 * 1024)
+        ));
+    }
+
+    if let Some(duration) = encoded_duration_secs(audio_data) {
+        if duration > MAX_AUDIO_DURATION_SECS {
+            return Err(format!(
+                "Recording is too long to transcribe ({:.0} min, limit is {:.0} min)",
+                duration / 60.0,
+                MAX_AUDIO_DURATION_SECS / 60.0
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn transcribe_audio_with_retry(
     audio_data: Vec<u8>,
     subscription_key: &str,
@@ -131,29 +221,12 @@ pub async fn transcribe_audio_with_retry(
     languages: &[String],  // Changed to support multiple languages
     multilingual: bool,     // When true, send empty locales for multi-lingual model
     max_retries: u32,
-) -> Result<String, String> {
-    for attempt in 0..max_retries {
-        match transcribe_audio(
-            audio_data.clone(),
-            subscription_key,
-            region,
-            languages,
-            multilingual,
-        )
-        .await
-        {
-            Ok(result) => return Ok(result),
-            Err(e) if attempt < max_retries - 1 => {
-                log::warn!("Transcription attempt {} failed: {}. Retrying...", attempt + 1, e);
-                tokio::time::sleep(tokio::time::Duration::from_secs(2_u64.pow(attempt))).await;
-            }
-            Err(e) => {
-                return Err(format!(
-                    "Transcription failed after {} attempts: {}",
-                    max_retries, e
-                ))
-            }
-        }
-    }
-    Err("Unexpected error in retry logic".to_string())
+) -> Result<Transcript, String> {
+    // Retrying won't help if the service is going to refuse the file
+    check_upload_limits(&audio_data)?;
+
+    with_retry(max_retries, || {
+        transcribe_audio(audio_data.clone(), subscription_key, region, languages, multilingual)
+    })
+    .await
 }
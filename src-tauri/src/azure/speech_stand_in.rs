@@ -0,0 +1,138 @@
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::speech_stream::{parse_audio_message, parse_message, text_message, WAV_HEADER_LEN};
+use crate::audio::TARGET_SAMPLE_RATE;
+
+const NUMBER_WORDS: [&str; 10] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten"];
+
+/// A local stand-in for the Azure Speech WebSocket service, for exercising
+/// streaming recognition without a key or network. It checks the client's
+/// framing and "recognizes" one word per second of audio ("one two three"),
+/// sending a hypothesis after each word and the phrase when the audio ends.
+///
+/// Binds `addr` (e.g. "127.0.0.1:0") and serves connections in the
+/// background; returns the bound address. Must be called within a Tokio runtime.
+pub async fn spawn(addr: &str) -> Result<SocketAddr, String> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind speech stand-in on {}: {}", addr, e))?;
+    let local = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get speech stand-in address: {}", e))?;
+    println!(">>> Speech stand-in listening on ws://{}", local);
+
+    tokio::spawn(async move {
+        while let Ok((stream, peer)) = listener.accept().await {
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream).await {
+                    println!(">>> Speech stand-in: connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    });
+    Ok(local)
+}
+
+async fn serve(stream: TcpStream) -> Result<(), String> {
+    let mut socket = tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(|e| format!("Handshake failed: {}", e))?;
+
+    let mut configured = false;
+    let mut request_id = String::new();
+    let mut audio_bytes = 0usize;
+    let mut words = 0usize;
+    let bytes_per_word = TARGET_SAMPLE_RATE as usize * 2;
+
+    while let Some(message) = socket.next().await {
+        let message = message.map_err(|e| format!("Receive failed: {}", e))?;
+        match message {
+            Message::Text(text) => {
+                let (path, _) = parse_message(&text);
+                if path != "speech.config" {
+                    return Err(format!("Unexpected text message '{}'", path));
+                }
+                request_id = header_value(&text, "x-requestid").unwrap_or_default();
+                configured = true;
+            }
+            Message::Binary(data) => {
+                let (path, payload) = parse_audio_message(&data).ok_or("Malformed audio message")?;
+                if !configured || path != "audio" {
+                    return Err(format!("Unexpected binary message '{}'", path));
+                }
+
+                if payload.is_empty() {
+                    // End of audio: the last partial word counts too
+                    let partial_word_bytes = audio_bytes % bytes_per_word;
+                    if partial_word_bytes > 0 {
+                        words += 1;
+                    }
+                    let text = transcript(words);
+                    let phrase = serde_json::json!({ "RecognitionStatus": "Success", "DisplayText": text });
+                    let end = serde_json::json!({ "RecognitionStatus": "EndOfDictation" });
+                    for (path, body) in [("speech.phrase", phrase.to_string()), ("speech.phrase", end.to_string()), ("turn.end", "{}".to_string())] {
+                        send(&mut socket, path, &request_id, &body).await?;
+                    }
+                    continue;
+                }
+
+                let skip = if audio_bytes == 0 { WAV_HEADER_LEN.min(payload.len()) } else { 0 };
+                if skip > 0 && !payload.starts_with(b"RIFF") {
+                    return Err("First audio message has no WAV header".to_string());
+                }
+                audio_bytes += payload.len() - skip;
+                while (words + 1) * bytes_per_word <= audio_bytes {
+                    words += 1;
+                    let hypothesis = serde_json::json!({ "Text": words_so_far(words) });
+                    send(&mut socket, "speech.hypothesis", &request_id, &hypothesis.to_string()).await?;
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+async fn send(
+    socket: &mut tokio_tungstenite::WebSocketStream<TcpStream>,
+    path: &str,
+    request_id: &str,
+    body: &str,
+) -> Result<(), String> {
+    socket
+        .send(Message::Text(text_message(path, request_id, body)))
+        .await
+        .map_err(|e| format!("Send failed: {}", e))
+}
+
+fn header_value(message: &str, name: &str) -> Option<String> {
+    message
+        .split("\r\n\r\n")
+        .next()?
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+        })
+}
+
+fn words_so_far(count: usize) -> String {
+    (0..count)
+        .map(|i| NUMBER_WORDS.get(i).map_or_else(|| (i + 1).to_string(), |w| w.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// "One two three." for three words, the way a display-text phrase looks.
+fn transcript(words: usize) -> String {
+    let text = words_so_far(words);
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}.", first.to_uppercase(), chars.as_str()),
+        None => String::new(),
+    }
+}
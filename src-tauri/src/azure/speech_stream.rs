@@ -0,0 +1,383 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::audio::{AudioTap, TARGET_SAMPLE_RATE};
+use crate::transcription::Transcript;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// How long the service gets to send the last phrase after the audio ends
+const FINAL_RESULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The text recognized so far: finished phrases plus the current hypothesis.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialTranscript {
+    pub text: String,
+}
+
+pub type PartialHandler = Arc<dyn Fn(PartialTranscript) + Send + Sync>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Hypothesis {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Phrase {
+    recognition_status: String,
+    #[serde(default)]
+    display_text: String,
+}
+
+/// Conversation-mode recognition endpoint for `region`. Streaming recognizes a
+/// single `language`.
+pub fn endpoint_url(region: &str, language: &str) -> String {
+    format!(
+        "wss://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1?language={}&format=simple",
+        region, language
+    )
+}
+
+/// Real-time recognition over the Azure Speech WebSocket protocol: audio is
+/// sent while recording and partial hypotheses come back as the user talks.
+/// The connection is fed by an `AudioTap`. Dropping the tap (the
+/// recording finished) ends the audio; `finish` then waits for the last phrase.
+pub struct StreamingSession {
+    task: JoinHandle<Result<Transcript, String>>,
+}
+
+impl StreamingSession {
+    /// Connect to `url` in the background and return the tap to record into.
+    /// Audio arriving before the connection is up is queued, not lost. Must be
+    /// called from within a Tokio runtime.
+    pub fn start(url: String, key: String, language: String, on_partial: PartialHandler) -> (Self, AudioTap) {
+        let (tap, audio) = mpsc::unbounded_channel();
+        let task = tokio::spawn(async move {
+            let result = run_stream(&url, &key, &language, audio, on_partial).await;
+            if let Err(ref e) = result {
+                log::warn!("Streaming recognition failed: {}", e);
+                println!(">>> WARN: Streaming recognition failed: {}", e);
+            }
+            result
+        });
+        (Self { task }, tap)
+    }
+
+    /// Wait for the final transcript. The tap must have been dropped, or this
+    /// only returns at the timeout.
+    pub async fn finish(mut self) -> Result<Transcript, String> {
+        match tokio::time::timeout(FINAL_RESULT_TIMEOUT, &mut self.task).await {
+            Ok(result) => result.map_err(|e| format!("Streaming task failed: {}", e))?,
+            Err(_) => Err("Timed out waiting for the final streaming result".to_string()),
+        }
+    }
+}
+
+impl Drop for StreamingSession {
+    fn drop(&mut self) {
+        // Closes the connection if the session is abandoned (e.g. the recording failed)
+        self.task.abort();
+    }
+}
+
+async fn run_stream(
+    url: &str,
+    key: &str,
+    language: &str,
+    mut audio: mpsc::UnboundedReceiver<Vec<f32>>,
+    on_partial: PartialHandler,
+) -> Result<Transcript, String> {
+    let mut request = url
+        .into_client_request()
+        .map_err(|e| format!("Invalid streaming endpoint {}: {}", url, e))?;
+    let headers = request.headers_mut();
+    headers.insert(
+        "Ocp-Apim-Subscription-Key",
+        HeaderValue::from_str(key).map_err(|_| "Invalid Azure Speech key".to_string())?,
+    );
+    headers.insert(
+        "X-ConnectionId",
+        HeaderValue::from_str(&new_id()).map_err(|e| format!("Invalid connection id: {}", e))?,
+    );
+
+    log::info!("Connecting to streaming recognition (language: {})", language);
+    println!(">>> Connecting to streaming recognition (language: {})", language);
+    let (socket, _) = tokio::time::timeout(CONNECT_TIMEOUT, tokio_tungstenite::connect_async(request))
+        .await
+        .map_err(|_| "Timed out connecting to streaming recognition".to_string())?
+        .map_err(|e| format!("Failed to connect to streaming recognition: {}", e))?;
+    let (mut sink, mut stream) = socket.split();
+
+    let request_id = new_id();
+    sink.send(Message::Text(text_message("speech.config", &request_id, &speech_config())))
+        .await
+        .map_err(|e| format!("Failed to send speech config: {}", e))?;
+
+    let mut audio_open = true;
+    let mut samples_sent: u64 = 0;
+    let mut phrases: Vec<String> = Vec::new();
+
+    loop {
+        tokio::select! {
+            chunk = audio.recv(), if audio_open => {
+                let payload = match chunk {
+                    Some(samples) => {
+                        let mut payload = Vec::with_capacity(samples.len() * 2 + WAV_HEADER_LEN);
+                        // The first audio message starts with a WAV header describing the format
+                        if samples_sent == 0 {
+                            payload.extend_from_slice(&wav_header());
+                        }
+                        samples_sent += samples.len() as u64;
+                        payload.extend(samples.iter().flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes()));
+                        payload
+                    }
+                    None => {
+                        // An empty audio message marks the end of the stream
+                        audio_open = false;
+                        println!(">>> Streamed {:.1}s of audio, waiting for the final result", samples_sent as f64 / TARGET_SAMPLE_RATE as f64);
+                        if samples_sent == 0 {
+                            return Err("No audio was streamed".to_string());
+                        }
+                        Vec::new()
+                    }
+                };
+                sink.send(Message::Binary(audio_message(&request_id, &payload)))
+                    .await
+                    .map_err(|e| format!("Failed to send audio: {}", e))?;
+            }
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(frame))) => {
+                        let reason = frame.map(|f| f.reason.to_string()).unwrap_or_default();
+                        return Err(format!("Streaming recognition closed by the service: {}", reason));
+                    }
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(format!("Streaming recognition failed: {}", e)),
+                    None => return Err("Streaming recognition connection closed".to_string()),
+                };
+
+                let (path, body) = parse_message(&text);
+                match path.as_str() {
+                    "speech.hypothesis" => {
+                        let hypothesis: Hypothesis = serde_json::from_str(body)
+                            .map_err(|e| format!("Parse error: {}", e))?;
+                        let text = phrases.iter().map(String::as_str).chain([hypothesis.text.as_str()]).collect::<Vec<_>>().join(" ");
+                        on_partial(PartialTranscript { text });
+                    }
+                    "speech.phrase" => {
+                        let phrase: Phrase = serde_json::from_str(body)
+                            .map_err(|e| format!("Parse error: {}", e))?;
+                        match phrase.recognition_status.as_str() {
+                            "Success" if !phrase.display_text.is_empty() => {
+                                phrases.push(phrase.display_text);
+                                on_partial(PartialTranscript { text: phrases.join(" ") });
+                            }
+                            // Silence or the end of the stream, nothing recognized
+                            "Success" | "NoMatch" | "InitialSilenceTimeout" | "BabbleTimeout" | "EndOfDictation" => {}
+                            status => return Err(format!("Streaming recognition error: {}", status)),
+                        }
+                    }
+                    // The service ends the turn once it has processed the end of the audio
+                    "turn.end" if !audio_open => break,
+                    "turn.end" => return Err("Streaming recognition ended before the recording did".to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+    let text = phrases.join(" ");
+    if text.is_empty() {
+        return Err("No transcription text in response".to_string());
+    }
+    log::info!("Streaming transcription successful");
+    Ok(Transcript {
+        text,
+        language: Some(language.to_string()),
+        duration_secs: Some(samples_sent as f64 / TARGET_SAMPLE_RATE as f64),
+        // Offsets count from the start of the streamed audio, which isn't trimmed
+        // like the recording, so they wouldn't line up with its playback
+        segments: Vec::new(),
+    })
+}
+
+/// Request and connection ids are UUIDs without dashes.
+fn new_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string().to_uppercase()
+}
+
+fn timestamp() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+fn speech_config() -> String {
+    serde_json::json!({
+        "context": {
+            "system": { "name": "FluxVoice", "version": env!("CARGO_PKG_VERSION") },
+            "os": { "platform": std::env::consts::OS, "name": std::env::consts::OS, "version": "" },
+            "audio": { "source": { "type": "Microphones" } },
+        }
+    })
+    .to_string()
+}
+
+/// A text message: CRLF-separated headers, a blank line, then the JSON body.
+pub(crate) fn text_message(path: &str, request_id: &str, body: &str) -> String {
+    format!(
+        "Path: {}\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: application/json\r\n\r\n{}",
+        path,
+        request_id,
+        timestamp(),
+        body
+    )
+}
+
+/// A binary audio message: big-endian u16 header length, the headers, then the audio.
+fn audio_message(request_id: &str, payload: &[u8]) -> Vec<u8> {
+    let headers = format!(
+        "Path: audio\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: audio/x-wav\r\n",
+        request_id,
+        timestamp()
+    );
+    let mut message = Vec::with_capacity(2 + headers.len() + payload.len());
+    message.extend_from_slice(&(headers.len() as u16).to_be_bytes());
+    message.extend_from_slice(headers.as_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+/// Split a text message into its lowercased `Path` header and body.
+pub(crate) fn parse_message(message: &str) -> (String, &str) {
+    let (headers, body) = message.split_once("\r\n\r\n").unwrap_or((message, ""));
+    let path = headers
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("path").then(|| value.trim().to_lowercase())
+        })
+        .unwrap_or_default();
+    (path, body)
+}
+
+/// Split a binary message into its lowercased `Path` header and payload.
+pub(crate) fn parse_audio_message(message: &[u8]) -> Option<(String, &[u8])> {
+    let header_len = u16::from_be_bytes([*message.first()?, *message.get(1)?]) as usize;
+    let headers = std::str::from_utf8(message.get(2..2 + header_len)?).ok()?;
+    let (path, _) = parse_message(headers);
+    Some((path, &message[2 + header_len..]))
+}
+
+pub(crate) const WAV_HEADER_LEN: usize = 44;
+
+/// Header for an open-ended 16 kHz, 16-bit mono PCM stream (sizes left at 0).
+fn wav_header() -> [u8; WAV_HEADER_LEN] {
+    let mut header = [0u8; WAV_HEADER_LEN];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[8..16].copy_from_slice(b"WAVEfmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    header[20..22].copy_from_slice(&1u16.to_le_bytes()); // PCM
+    header[22..24].copy_from_slice(&1u16.to_le_bytes()); // Mono
+    header[24..28].copy_from_slice(&TARGET_SAMPLE_RATE.to_le_bytes());
+    header[28..32].copy_from_slice(&(TARGET_SAMPLE_RATE * 2).to_le_bytes());
+    header[32..34].copy_from_slice(&2u16.to_le_bytes()); // Block align
+    header[34..36].copy_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    header[36..40].copy_from_slice(b"data");
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::source::Signal;
+    use crate::audio::{self, RecordingOptions, SourceKind};
+    use crate::azure::speech_stand_in;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    fn stand_in_url(addr: std::net::SocketAddr) -> String {
+        format!("ws://{}/speech/recognition/conversation/cognitiveservices/v1", addr)
+    }
+
+    /// Start a session on `url` that collects every partial result.
+    fn start_session(url: String) -> (StreamingSession, AudioTap, Arc<Mutex<Vec<String>>>) {
+        let partials = Arc::new(Mutex::new(Vec::new()));
+        let collected = partials.clone();
+        let (session, tap) = StreamingSession::start(
+            url,
+            "stand-in".to_string(),
+            "en-US".to_string(),
+            Arc::new(move |partial| collected.lock().unwrap().push(partial.text)),
+        );
+        (session, tap, partials)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn streams_synthetic_source_to_stand_in() {
+        let addr = speech_stand_in::spawn("127.0.0.1:0").await.unwrap();
+        let (session, tap, partials) = start_session(stand_in_url(addr));
+
+        // 2.5 s of audio: the stand-in hears two whole words and a partial third
+        let options = RecordingOptions {
+            source: SourceKind::Synthetic {
+                signal: Signal::Sine { frequency_hz: 440.0, amplitude: 0.3 },
+                sample_rate: 48000,
+                channels: 2,
+                duration_ms: 2500,
+                realtime: false,
+            },
+            trim_silence: false,
+            max_duration_secs: 60,
+            audio_tap: Some(tap),
+            ..Default::default()
+        };
+        // Recording blocks, and the options (with the tap) are dropped when it returns
+        tokio::task::spawn_blocking(move || audio::record_source(&options))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let transcript = session.finish().await.unwrap();
+        assert_eq!(transcript.text, "One two three.");
+        assert_eq!(transcript.language.as_deref(), Some("en-US"));
+        let duration = transcript.duration_secs.unwrap();
+        assert!((duration - 2.5).abs() < 0.001, "streamed {} s", duration);
+
+        let partials = partials.lock().unwrap();
+        assert_eq!(*partials, ["one", "one two", "One two three."]);
+    }
+
+    #[tokio::test]
+    async fn turn_end_before_the_audio_ends_is_an_error() {
+        // A service that ends the turn as soon as it has the config
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            socket.next().await;
+            let turn_end = text_message("turn.end", "request", "{}");
+            socket.send(Message::Text(turn_end)).await.unwrap();
+            // Stay connected until the client hangs up
+            while let Some(Ok(_)) = socket.next().await {}
+        });
+
+        let (session, tap, partials) = start_session(stand_in_url(addr));
+        tap.send(vec![0.1; TARGET_SAMPLE_RATE as usize]).unwrap();
+
+        // The tap is still open, so the recording hasn't finished
+        let error = session.finish().await.unwrap_err();
+        assert_eq!(error, "Streaming recognition ended before the recording did");
+        assert!(partials.lock().unwrap().is_empty());
+        drop(tap);
+    }
+}
@@ -1,19 +1,37 @@
-use crate::audio::AudioRecorder;
-use crate::azure::{openai, speech};
+use crate::audio::{self, AudioRecorder, CaptureStats, InputDeviceInfo, RecordingOptions, SourceKind};
+use crate::azure::openai;
+use crate::azure::speech_stream::{self, StreamingSession};
 use crate::config::{store, AppConfig};
+use crate::hotkey::{parse_pause_hotkey, HotkeyAction, HotkeyManager};
 use crate::input::TextInjector;
+use crate::transcription::{self, Transcript, TranscriptSegment, TranscriptionOptions};
+use global_hotkey::hotkey::{Code, Modifiers};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::Mutex;
-use tauri::State;
+use tauri::{Emitter, State};
 use serde::{Deserialize, Serialize};
 
 // Global lock to prevent concurrent transcription operations
 static IS_TRANSCRIBING: AtomicBool = AtomicBool::new(false);
 
+// Resets IS_TRANSCRIBING when dropped
+struct TranscriptionGuard;
+
+impl Drop for TranscriptionGuard {
+    fn drop(&mut self) {
+        IS_TRANSCRIBING.store(false, Ordering::SeqCst);
+    }
+}
+
 pub struct AppState {
     pub recorder: Arc<Mutex<AudioRecorder>>,
     pub injector: Arc<Mutex<TextInjector>>,
+    // Lossless copy of the last recording, attached to its transcription result
+    pub last_recording_path: Arc<Mutex<Option<String>>>,
+    // Streaming recognition of the current recording, finished by transcribe_and_insert
+    pub streaming: Arc<Mutex<Option<StreamingSession>>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -23,6 +41,9 @@ pub struct TranscriptionResult {
     pub final_text: String,
     pub post_processing_mode: String,
     pub warning: Option<String>,
+    pub recording_path: Option<String>,
+    pub source: Option<String>,  // "microphone", "system", "microphone+system" or "file"
+    pub segments: Vec<TranscriptSegment>,  // Phrase and word timing of `original`, if the engine gave any
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,11 +53,18 @@ pub struct TranscriptionHistoryItem {
     pub final_text: String,
     pub timestamp: u64,
     pub audio_data: Option<Vec<u8>>,
+    #[serde(default)]
+    pub recording_path: Option<String>,  // Lossless WAV copy, if one was kept
+    #[serde(default)]
+    pub source: Option<String>,  // Where the audio came from, see TranscriptionResult
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,  // Offsets into `audio_data`
 }
 
 const HISTORY_STORE_FILE: &str = "history.json";
 const STATS_STORE_FILE: &str = "stats.json";
 const WINDOW_STORE_FILE: &str = "window.json";
+const RECORDINGS_DIR: &str = "recordings";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DailyStats {
@@ -60,20 +88,209 @@ pub async fn get_config(app: tauri::AppHandle) -> Result<AppConfig, String> {
 }
 
 #[tauri::command]
-pub async fn save_config_cmd(app: tauri::AppHandle, config: AppConfig) -> Result<(), String> {
-    store::save_config(&app, &config)
+pub async fn save_config_cmd(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config: AppConfig,
+) -> Result<(), String> {
+    let pause_hotkey = parse_pause_hotkey(&config.hotkey, config.pause_hotkey.as_ref())?;
+    store::save_config(&app, &config)?;
+
+    // Open or release the warm microphone to match the new settings
+    let mut recorder = state.recorder.lock().await;
+    recorder.apply_warm_microphone(&RecordingOptions::from(&config));
+    drop(recorder);
+
+    apply_pause_hotkey(&app, pause_hotkey)
+        .await
+        .map_err(|e| format!("Settings saved, but the pause hotkey could not be set: {}", e))
+}
+
+/// Bind the pause hotkey to `hotkey`, or unbind it if None.
+async fn apply_pause_hotkey(app: &tauri::AppHandle, hotkey: Option<(Modifiers, Code)>) -> Result<(), String> {
+    use tauri::Manager;
+
+    // Not managed yet while startup registration is still running; it reads the saved config
+    let Some(hotkey_manager) = app.try_state::<Arc<Mutex<HotkeyManager>>>() else {
+        return Ok(());
+    };
+    let mut hotkey_manager = hotkey_manager.lock().await;
+    match hotkey {
+        Some((modifiers, key)) => {
+            hotkey_manager.register(HotkeyAction::Pause, modifiers, key).await?;
+            println!(">>> Pause hotkey registered: {:?} + {:?}", modifiers, key);
+        }
+        None => hotkey_manager.unregister_action(HotkeyAction::Pause).await?,
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_input_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    audio::list_input_devices()
+}
+
+/// Devices system audio can be recorded from (monitor sources, loopback drivers
+/// or, on Windows, output devices).
+#[tauri::command]
+pub async fn list_loopback_devices() -> Result<Vec<InputDeviceInfo>, String> {
+    audio::list_loopback_devices()
 }
 
 #[tauri::command]
-pub async fn start_recording(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn start_recording(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let config = store::load_config(&app)?;
+    let mut options = RecordingOptions::from(&config);
+
+    // A session left over from a recording that was never transcribed is dropped here
+    let mut streaming = state.streaming.lock().await;
+    *streaming = None;
+    if config.azure.streaming && config.provider == "azure" && !config.azure.speech_key.is_empty() {
+        let (session, tap) = start_streaming(&app, &config);
+        options.audio_tap = Some(tap);
+        *streaming = Some(session);
+    }
+
     let mut recorder = state.recorder.lock().await;
-    recorder.start_recording()
+    let result = recorder.start_recording(options);
+    if result.is_err() {
+        *streaming = None;
+    }
+    result
+}
+
+/// Connect streaming recognition for a new recording; partial results are
+/// emitted as `transcription-partial` events.
+fn start_streaming(app: &tauri::AppHandle, config: &AppConfig) -> (StreamingSession, audio::AudioTap) {
+    // Streaming recognizes a single language, the first one configured
+    let language = config
+        .language
+        .speech_languages
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "en-US".to_string());
+    let url = speech_stream::endpoint_url(&config.azure.speech_region, &language);
+
+    let app = app.clone();
+    StreamingSession::start(
+        url,
+        config.azure.speech_key.clone(),
+        language,
+        Arc::new(move |partial| {
+            if let Err(e) = app.emit("transcription-partial", partial) {
+                log::error!("Failed to emit partial transcript: {}", e);
+            }
+        }),
+    )
 }
 
 #[tauri::command]
-pub async fn stop_recording(state: State<'_, AppState>) -> Result<Vec<u8>, String> {
+pub async fn stop_recording(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<u8>, String> {
+    let (result, lossless) = {
+        let mut recorder = state.recorder.lock().await;
+        let result = recorder.stop_recording();
+        (result, recorder.take_lossless_copy())
+    };
+
+    let mut recording_path = None;
+    if let (Ok(_), Some(pcm)) = (&result, lossless) {
+        match save_lossless_copy(&app, &pcm) {
+            Ok(path) => recording_path = Some(path),
+            // Keeping the copy is best effort, the recording itself is fine
+            Err(e) => log::warn!("Failed to save lossless copy: {}", e),
+        }
+    }
+    *state.last_recording_path.lock().await = recording_path;
+    if result.is_err() {
+        // Nothing will be transcribed, close the stream
+        *state.streaming.lock().await = None;
+    }
+
+    result
+}
+
+fn save_lossless_copy(app: &tauri::AppHandle, pcm: &[i16]) -> Result<String, String> {
+    use tauri::Manager;
+
+    let config = store::load_config(app)?;
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join(RECORDINGS_DIR);
+
+    let path = audio::archive::save_wav(&dir, pcm)?;
+    let max_bytes = config.audio.lossless_retention_mb as u64 * 1024 * 1024;
+    let deleted = audio::archive::enforce_retention(&dir, max_bytes).unwrap_or_else(|e| {
+        log::warn!("{}", e);
+        Vec::new()
+    });
+    let kept = !deleted.contains(&path);
+    if !deleted.is_empty() {
+        let deleted: Vec<String> = deleted.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+        if let Err(e) = forget_recordings(app, &deleted) {
+            log::warn!("Failed to unlink deleted recordings from history: {}", e);
+        }
+        // The history page drops its links to them too
+        if let Err(e) = app.emit("recordings-deleted", &deleted) {
+            log::error!("Failed to emit recordings-deleted event: {}", e);
+        }
+    }
+    if !kept {
+        return Err("Recording is larger than the lossless retention limit".to_string());
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Clear `recording_path` on history items whose WAV copy was deleted.
+fn forget_recordings(app: &tauri::AppHandle, deleted: &[String]) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app
+        .store(HISTORY_STORE_FILE)
+        .map_err(|e| format!("Failed to open history store: {}", e))?;
+
+    let mut history: Vec<TranscriptionHistoryItem> = store
+        .get("history")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    let mut changed = false;
+    for item in &mut history {
+        if item.recording_path.as_ref().is_some_and(|path| deleted.contains(path)) {
+            item.recording_path = None;
+            changed = true;
+        }
+    }
+    if !changed {
+        return Ok(());
+    }
+
+    let history_value = serde_json::to_value(&history)
+        .map_err(|e| format!("Failed to serialize history: {}", e))?;
+    store.set("history", history_value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save history store: {}", e))
+}
+
+/// Pause the current recording without ending it; see `AudioRecorder::pause_recording`.
+#[tauri::command]
+pub async fn pause_recording(state: State<'_, AppState>) -> Result<(), String> {
+    let mut recorder = state.recorder.lock().await;
+    recorder.pause_recording()
+}
+
+#[tauri::command]
+pub async fn resume_recording(state: State<'_, AppState>) -> Result<(), String> {
     let mut recorder = state.recorder.lock().await;
-    recorder.stop_recording()
+    recorder.resume_recording()
 }
 
 #[tauri::command]
@@ -82,6 +299,13 @@ pub async fn get_audio_level(state: State<'_, AppState>) -> Result<f32, String>
     Ok(recorder.get_audio_level())
 }
 
+/// Input buffer overruns of the current stream, for diagnosing dropouts.
+#[tauri::command]
+pub async fn get_capture_stats(state: State<'_, AppState>) -> Result<CaptureStats, String> {
+    let recorder = state.recorder.lock().await;
+    Ok(recorder.capture_stats())
+}
+
 #[tauri::command]
 pub async fn transcribe_and_insert(
     app: tauri::AppHandle,
@@ -95,32 +319,96 @@ pub async fn transcribe_and_insert(
     }
 
     // Use a guard to ensure IS_TRANSCRIBING is reset even if we return early
-    struct TranscriptionGuard;
-    impl Drop for TranscriptionGuard {
-        fn drop(&mut self) {
-            IS_TRANSCRIBING.store(false, Ordering::SeqCst);
-        }
-    }
     let _guard = TranscriptionGuard;
 
     // Load config
     let config = store::load_config(&app)?;
 
-    // Validate Azure credentials
-    if config.azure.speech_key.is_empty() {
-        return Err("Azure Speech key not configured".to_string());
+    // Collect warnings raised while recording (e.g. microphone fallback)
+    let (warnings, source) = {
+        let recorder = state.recorder.lock().await;
+        (recorder.take_warnings(), recorder.last_source())
+    };
+
+    let insert = config.features.auto_insert_enabled;
+    let streaming = state.streaming.lock().await.take();
+    let mut result = transcribe_and_process(&state, &config, audio_data, streaming, warnings, insert).await?;
+    result.recording_path = state.last_recording_path.lock().await.take();
+    result.source = source.map(str::to_string);
+    Ok(result)
+}
+
+/// Transcribe an audio file from disk (WAV, FLAC, MP3 or OGG/Opus) through the
+/// same pipeline as a recording. Text is only inserted when `insert` is true.
+#[tauri::command]
+pub async fn transcribe_file(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    insert: Option<bool>,
+) -> Result<TranscriptionResult, String> {
+    if IS_TRANSCRIBING.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        log::warn!("transcribe_file called while another transcription is in progress - ignoring");
+        return Err("Transcription already in progress".to_string());
     }
+    let _guard = TranscriptionGuard;
 
-    // Transcribe audio with retry
-    let transcript = speech::transcribe_audio_with_retry(
-        audio_data,
-        &config.azure.speech_key,
-        &config.azure.speech_region,
-        &config.language.speech_languages,
-        config.language.multilingual,
-        2, // max retries (1 initial + 1 retry)
-    )
-    .await?;
+    let config = store::load_config(&app)?;
+
+    log::info!("Transcribing file: {}", path);
+    println!(">>> Transcribing file: {}", path);
+
+    // Decode, downmix, resample and encode off the async runtime
+    let mut options = RecordingOptions::from(&config);
+    options.source = SourceKind::File { path: PathBuf::from(&path), realtime: false };
+    let source = options.source.label();
+    // No duration limit; record_source fails if the encoded audio outgrows its memory cap
+    options.max_duration_secs = u32::MAX;
+    options.pipeline.keep_lossless = false;
+    let audio_data = tokio::task::spawn_blocking(move || audio::record_source(&options))
+        .await
+        .map_err(|e| format!("Audio decoding task failed: {}", e))??;
+
+    let mut result =
+        transcribe_and_process(&state, &config, audio_data, None, Vec::new(), insert.unwrap_or(false)).await?;
+    result.source = Some(source.to_string());
+    Ok(result)
+}
+
+/// Transcribe encoded audio (or take the result of the recording's streaming
+/// session), run the configured post-processing and optionally insert the
+/// result into the active window.
+async fn transcribe_and_process(
+    state: &AppState,
+    config: &AppConfig,
+    audio_data: Vec<u8>,
+    streaming: Option<StreamingSession>,
+    mut warnings: Vec<String>,
+    insert: bool,
+) -> Result<TranscriptionResult, String> {
+    // Fails early if the provider isn't configured (e.g. no Azure key)
+    let provider = transcription::provider_from_config(config)?;
+
+    let streamed = match streaming {
+        Some(session) => match session.finish().await {
+            Ok(transcript) => Some(transcript),
+            Err(e) => {
+                log::warn!("No streaming result ({}), transcribing the recording instead", e);
+                println!(">>> WARN: No streaming result ({}), transcribing the recording instead", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let Transcript { text: transcript, segments, .. } = match streamed {
+        Some(transcript) => transcript,
+        None => {
+            println!(">>> Transcribing with provider: {}", provider.name());
+            provider
+                .transcribe(audio_data, &TranscriptionOptions::from(config))
+                .await?
+        }
+    };
 
     log::info!("Transcription: {}", transcript);
 
@@ -129,8 +417,6 @@ pub async fn transcribe_and_insert(
     log::info!(">>> Post-processing mode from config: '{}'", mode);
     println!(">>> Post-processing mode from config: '{}'", mode);
 
-    let mut warning: Option<String> = None;
-
     let (final_text, polished) = if !config.azure.openai_key.is_empty()
         && !config.azure.openai_endpoint.is_empty()
     {
@@ -154,7 +440,7 @@ pub async fn transcribe_and_insert(
                     Err(e) => {
                         log::warn!(">>> Failed to polish text: {}. Using original transcript.", e);
                         println!(">>> Failed to polish text: {}. Using original.", e);
-                        warning = Some(format!("Polish failed: {}", e));
+                        warnings.push(format!("Polish failed: {}", e));
                         (transcript.clone(), None)
                     }
                 }
@@ -180,7 +466,7 @@ pub async fn transcribe_and_insert(
                     Err(e) => {
                         log::warn!(">>> Failed to translate text: {}. Using original transcript.", e);
                         println!(">>> Failed to translate text: {}. Using original.", e);
-                        warning = Some(format!("Translation failed: {}", e));
+                        warnings.push(format!("Translation failed: {}", e));
                         (transcript.clone(), None)
                     }
                 }
@@ -198,7 +484,7 @@ pub async fn transcribe_and_insert(
     };
 
     // Insert into active window if enabled
-    if config.features.auto_insert_enabled {
+    if insert {
         let mut injector = state.injector.lock().await;
         injector.inject_text(&final_text)?;
     }
@@ -208,7 +494,10 @@ pub async fn transcribe_and_insert(
         polished,
         final_text,
         post_processing_mode: mode,
-        warning,
+        warning: if warnings.is_empty() { None } else { Some(warnings.join("; ")) },
+        recording_path: None,
+        source: None,
+        segments,
     })
 }
 
@@ -280,11 +569,18 @@ pub async fn load_history(app: tauri::AppHandle) -> Result<Vec<TranscriptionHist
         .store(HISTORY_STORE_FILE)
         .map_err(|e| format!("Failed to open history store: {}", e))?;
 
-    let history: Vec<TranscriptionHistoryItem> = store
+    let mut history: Vec<TranscriptionHistoryItem> = store
         .get("history")
         .and_then(|v| serde_json::from_value(v.clone()).ok())
         .unwrap_or_default();
 
+    // WAV copies may have been deleted since (e.g. by hand), don't link to them
+    for item in &mut history {
+        if item.recording_path.as_ref().is_some_and(|path| !std::path::Path::new(path).exists()) {
+            item.recording_path = None;
+        }
+    }
+
     log::info!("Loaded {} history items from disk", history.len());
 
     Ok(history)
@@ -1,13 +1,31 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfig {
+    #[serde(default = "default_provider")]
+    pub provider: String,  // Speech-to-text backend, see transcription::provider_from_config
     pub azure: AzureConfig,
+    #[serde(default)]
+    pub whisper: WhisperConfig,
+    #[serde(default)]
+    pub openai_transcription: OpenAiTranscriptionConfig,
     pub hotkey: HotkeyConfig,
+    // Pauses and resumes the current recording; None leaves it unbound
+    #[serde(default)]
+    pub pause_hotkey: Option<HotkeyConfig>,
     pub language: LanguageConfig,
     pub ui: UIConfig,
     pub features: FeatureConfig,
+    #[serde(default)]
+    pub audio: AudioConfig,
+    #[serde(default)]
+    pub encoding: EncodingConfig,
+}
+
+fn default_provider() -> String {
+    "azure".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +36,53 @@ pub struct AzureConfig {
     pub openai_endpoint: String,
     pub openai_key: String,
     pub openai_deployment: String,
+    // Recognize while recording over a WebSocket and show partial results; falls
+    // back to Fast Transcription of the whole recording if the stream fails
+    #[serde(default)]
+    pub streaming: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperConfig {
+    #[serde(default)]
+    pub model_path: String,  // ggml model file, e.g. ggml-base.en.bin
+    #[serde(default)]
+    pub threads: u32,  // 0 picks one per CPU core (up to 8)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiTranscriptionConfig {
+    // Up to the API version, e.g. https://api.openai.com/v1 or, for Azure OpenAI,
+    // https://<resource>.openai.azure.com/openai/deployments/<deployment>?api-version=2024-06-01
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_openai_model")]
+    pub model: String,  // Left out of the request when empty (Azure uses the deployment)
+    #[serde(default)]
+    pub api_key: String,  // Optional for self-hosted servers
+    #[serde(default)]
+    pub language: String,  // ISO 639-1 hint, e.g. "en"; empty uses the speech languages
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_openai_model() -> String {
+    "whisper-1".to_string()
+}
+
+impl Default for OpenAiTranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_openai_base_url(),
+            model: default_openai_model(),
+            api_key: String::new(),
+            language: String::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,21 +167,172 @@ impl FeatureConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioConfig {
+    #[serde(default)]
+    pub input_device: Option<String>,  // Device name; None uses the system default
+    #[serde(default = "default_capture_source")]
+    pub capture_source: String,  // "microphone", "system" (loopback) or "both"
+    #[serde(default)]
+    pub loopback_device: Option<String>,  // Monitor/loopback device name; None uses the default
+    #[serde(default = "default_true")]
+    pub reconnect_on_disconnect: bool,  // Continue on the system default device if the mic goes away mid-recording
+    // Device name -> "average", "loudest" or "channel-N" (1-based). Devices not listed average all channels.
+    #[serde(default)]
+    pub channel_modes: HashMap<String, String>,
+    #[serde(default = "default_true")]
+    pub trim_silence: bool,  // Trim leading/trailing silence and skip uploads with no speech
+    // Keep the microphone open between recordings so speech that starts right at the
+    // hotkey press isn't clipped. The OS will show the microphone as in use the whole
+    // time FluxVoice is running, so this is off by default.
+    #[serde(default)]
+    pub warm_microphone: bool,
+    #[serde(default = "default_pre_roll_ms")]
+    pub pre_roll_ms: u32,  // Audio from before the hotkey press prepended in warm mode
+    #[serde(default = "default_max_recording_secs")]
+    pub max_recording_secs: u32,  // Recording stops itself after this long (e.g. stuck hotkey)
+    // Hands-free: one hotkey press starts recording and it stops after trailing silence
+    #[serde(default)]
+    pub hands_free: bool,
+    #[serde(default = "default_silence_timeout_ms")]
+    pub silence_timeout_ms: u32,  // Silence after speech that ends a hands-free recording
+    #[serde(default = "default_hands_free_max_secs")]
+    pub hands_free_max_secs: u32,  // Hands-free recordings stop after this long even without a pause
+    #[serde(default)]
+    pub keep_lossless_copy: bool,  // Also save a 16 kHz WAV of each recording in the app data dir
+    #[serde(default = "default_lossless_retention_mb")]
+    pub lossless_retention_mb: u32,  // Oldest WAV copies are deleted beyond this total size
+    #[serde(default)]
+    pub noise_suppression: bool,
+    #[serde(default = "default_noise_suppression_strength")]
+    pub noise_suppression_strength: f32,  // 0.0 (gentle) - 1.0 (aggressive)
+    #[serde(default = "default_true")]
+    pub auto_gain: bool,  // Normalize quiet or loud speech before encoding
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_capture_source() -> String {
+    "microphone".to_string()
+}
+
+fn default_pre_roll_ms() -> u32 {
+    500
+}
+
+fn default_max_recording_secs() -> u32 {
+    600
+}
+
+fn default_silence_timeout_ms() -> u32 {
+    1500
+}
+
+fn default_hands_free_max_secs() -> u32 {
+    120
+}
+
+fn default_lossless_retention_mb() -> u32 {
+    500
+}
+
+fn default_noise_suppression_strength() -> f32 {
+    0.5
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            input_device: None,
+            capture_source: default_capture_source(),
+            loopback_device: None,
+            reconnect_on_disconnect: true,
+            channel_modes: HashMap::new(),
+            trim_silence: true,
+            warm_microphone: false,
+            pre_roll_ms: default_pre_roll_ms(),
+            max_recording_secs: default_max_recording_secs(),
+            hands_free: false,
+            silence_timeout_ms: default_silence_timeout_ms(),
+            hands_free_max_secs: default_hands_free_max_secs(),
+            keep_lossless_copy: false,
+            lossless_retention_mb: default_lossless_retention_mb(),
+            noise_suppression: false,
+            noise_suppression_strength: default_noise_suppression_strength(),
+            auto_gain: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingConfig {
+    #[serde(default = "default_upload_format")]
+    pub upload_format: String,  // "ogg" (Opus) or "wav" (16-bit PCM, larger but lossless)
+    #[serde(default = "default_bitrate_kbps")]
+    pub bitrate_kbps: u32,
+    #[serde(default = "default_complexity")]
+    pub complexity: u8,  // 0 (fastest) - 10 (best quality)
+    #[serde(default = "default_true")]
+    pub vbr: bool,
+    #[serde(default)]
+    pub fec: bool,  // In-band forward error correction
+    #[serde(default = "default_frame_ms")]
+    pub frame_ms: u32,  // 10, 20, 40 or 60
+}
+
+fn default_upload_format() -> String {
+    "ogg".to_string()
+}
+
+fn default_bitrate_kbps() -> u32 {
+    16
+}
+
+fn default_complexity() -> u8 {
+    10
+}
+
+fn default_frame_ms() -> u32 {
+    60
+}
+
+impl Default for EncodingConfig {
+    fn default() -> Self {
+        Self {
+            upload_format: default_upload_format(),
+            bitrate_kbps: default_bitrate_kbps(),
+            complexity: default_complexity(),
+            vbr: true,
+            fec: false,
+            frame_ms: default_frame_ms(),
+        }
+    }
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            provider: default_provider(),
             azure: AzureConfig {
                 speech_key: String::new(),
                 speech_region: "eastus".to_string(),
                 openai_endpoint: String::new(),
                 openai_key: String::new(),
                 openai_deployment: "gpt-4".to_string(),
+                streaming: false,
             },
+            whisper: WhisperConfig::default(),
+            openai_transcription: OpenAiTranscriptionConfig::default(),
             hotkey: HotkeyConfig {
                 modifier1: "Ctrl".to_string(),
                 modifier2: Some("Shift".to_string()),
                 key: "Z".to_string(),
             },
+            pause_hotkey: None,
             language: LanguageConfig {
                 speech_languages: vec!["en-US".to_string()],
                 multilingual: false,
@@ -137,6 +353,8 @@ impl Default for AppConfig {
                 auto_insert_enabled: true,
                 text_polishing_enabled: None,
             },
+            audio: AudioConfig::default(),
+            encoding: EncodingConfig::default(),
         }
     }
 }
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::audio::decode;
+use crate::audio::encoder::{EncoderSettings, UploadFormat};
+use crate::audio::pipeline::PipelineOptions;
+use crate::audio::source::Signal;
+use crate::audio::{self, RecordingOptions, SourceKind, TARGET_SAMPLE_RATE};
+use crate::azure::speech_stream::{self, StreamingSession};
+use crate::azure::{speech, speech_stand_in};
+
+const USAGE: &str = "Usage: flux-voice --headless <audio file|sine|noise|silence> \
+[--duration-ms <ms>] [--out <file>] [--format <ogg|wav>] [--language <locale>] [--no-trim] [--verify] \
+[--stream] [--speech-stand-in]";
+
+/// Run the recording pipeline without a window: read audio from a file or a
+/// generated signal, encode it and, when `AZURE_SPEECH_KEY` is set, transcribe
+/// it and print the text. Returns the process exit code.
+///
+/// `--stream` transcribes with streaming recognition while the audio is
+/// processed, printing partial results. `--speech-stand-in` streams to a local
+/// stand-in for the service instead, so no key or network is needed.
+pub fn run(args: &[String]) -> i32 {
+    match run_inner(args) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
+
+fn run_inner(args: &[String]) -> Result<(), String> {
+    let mut input: Option<String> = None;
+    let mut duration_ms: u32 = 3000;
+    let mut out: Option<PathBuf> = None;
+    let mut language = "en-US".to_string();
+    let mut trim_silence = true;
+    let mut format = UploadFormat::Ogg;
+    let mut verify = false;
+    let mut stream = false;
+    let mut stand_in = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| USAGE.to_string());
+        match arg.as_str() {
+            "--headless" => input = Some(value()?),
+            "--duration-ms" => {
+                duration_ms = value()?.parse().map_err(|_| "Invalid --duration-ms".to_string())?
+            }
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--format" => format = UploadFormat::from_name(&value()?),
+            "--language" => language = value()?,
+            "--no-trim" => trim_silence = false,
+            "--verify" => verify = true,
+            "--stream" => stream = true,
+            "--speech-stand-in" => {
+                stream = true;
+                stand_in = true;
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let input = input.ok_or_else(|| USAGE.to_string())?;
+    if verify && trim_silence {
+        return Err("--verify needs --no-trim, trimmed output is shorter than its input".to_string());
+    }
+
+    let signal = match input.as_str() {
+        "sine" => Some(Signal::Sine { frequency_hz: 440.0, amplitude: 0.3 }),
+        "noise" => Some(Signal::WhiteNoise { amplitude: 0.3 }),
+        "silence" => Some(Signal::Silence),
+        _ => None,
+    };
+    let source = match signal {
+        Some(signal) => SourceKind::Synthetic {
+            signal,
+            sample_rate: 48000,
+            channels: 2,
+            duration_ms,
+            realtime: false,
+        },
+        None => SourceKind::File { path: PathBuf::from(&input), realtime: false },
+    };
+
+    let options = RecordingOptions {
+        source,
+        trim_silence,
+        max_duration_secs: 2 * 60 * 60,
+        pipeline: PipelineOptions {
+            encoder: EncoderSettings { format, ..Default::default() },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    if stream {
+        return run_streaming(options, language, stand_in);
+    }
+
+    let audio_data = audio::record_source(&options)?;
+    println!(">>> Encoded {} bytes of {:?}", audio_data.len(), format);
+
+    if let Some(path) = out {
+        std::fs::write(&path, &audio_data)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        println!(">>> Wrote {}", path.display());
+    }
+
+    if verify {
+        verify_round_trip(&options.source, &audio_data)?;
+    }
+
+    let Ok(key) = std::env::var("AZURE_SPEECH_KEY") else {
+        println!(">>> AZURE_SPEECH_KEY not set - skipping transcription");
+        return Ok(());
+    };
+    let region = std::env::var("AZURE_SPEECH_REGION").unwrap_or_else(|_| "eastus".to_string());
+
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to start async runtime: {}", e))?;
+    let transcript = runtime.block_on(speech::transcribe_audio_with_retry(
+        audio_data,
+        &key,
+        &region,
+        &[language],
+        false,
+        2,
+    ))?;
+    println!("{}", transcript.text);
+
+    Ok(())
+}
+
+/// Record `options.source` through a streaming recognition session and print
+/// the partial and final results.
+fn run_streaming(mut options: RecordingOptions, language: String, stand_in: bool) -> Result<(), String> {
+    let runtime = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to start async runtime: {}", e))?;
+    let _context = runtime.enter();
+
+    let (url, key) = if stand_in {
+        let addr = runtime.block_on(speech_stand_in::spawn("127.0.0.1:0"))?;
+        (format!("ws://{}/speech/recognition/conversation/cognitiveservices/v1", addr), "stand-in".to_string())
+    } else {
+        let key = std::env::var("AZURE_SPEECH_KEY").map_err(|_| "--stream needs AZURE_SPEECH_KEY".to_string())?;
+        let region = std::env::var("AZURE_SPEECH_REGION").unwrap_or_else(|_| "eastus".to_string());
+        (speech_stream::endpoint_url(&region, &language), key)
+    };
+
+    let (session, tap) = StreamingSession::start(
+        url,
+        key,
+        language,
+        Arc::new(|partial| println!(">>> Partial: {}", partial.text)),
+    );
+    options.audio_tap = Some(tap);
+    let audio_data = audio::record_source(&options);
+    // The session only sees the end of the audio once every tap is gone
+    options.audio_tap = None;
+    println!(">>> Encoded {} bytes", audio_data?.len());
+
+    let transcript = runtime.block_on(session.finish())?;
+    println!("{}", transcript.text);
+    Ok(())
+}
+
+/// Decode the encoded output and check it has exactly as many samples as the
+/// source, i.e. the pre-skip and end trimming are right.
+fn verify_round_trip(source: &SourceKind, encoded: &[u8]) -> Result<(), String> {
+    let (frames, sample_rate) = match source {
+        SourceKind::Synthetic { sample_rate, duration_ms, .. } => {
+            (*sample_rate as u64 * *duration_ms as u64 / 1000, *sample_rate)
+        }
+        SourceKind::File { path, .. } => {
+            let decoded = decode::decode_file(path)?;
+            ((decoded.samples.len() / decoded.channels as usize) as u64, decoded.sample_rate)
+        }
+        SourceKind::Microphone { .. } | SourceKind::Loopback { .. } | SourceKind::Mixed { .. } => {
+            return Err("Can't verify a live recording".to_string())
+        }
+    };
+    // The resampler emits ceil(frames * 16000 / rate) samples
+    let expected = (frames * TARGET_SAMPLE_RATE as u64).div_ceil(sample_rate as u64);
+
+    let decoded = decode::decode_bytes(encoded.to_vec(), None)?;
+    let decoded_frames = (decoded.samples.len() / decoded.channels as usize) as u64;
+    // Compare at the decoder's rate (48 kHz for Opus, 16 kHz for WAV)
+    let expected_decoded = expected * decoded.sample_rate as u64 / TARGET_SAMPLE_RATE as u64;
+
+    println!(
+        ">>> Round trip: {} samples expected, {} decoded at {} Hz",
+        expected_decoded, decoded_frames, decoded.sample_rate
+    );
+    if decoded_frames != expected_decoded {
+        return Err(format!(
+            "Round trip length mismatch: expected {} samples, decoded {}",
+            expected_decoded, decoded_frames
+        ));
+    }
+    Ok(())
+}
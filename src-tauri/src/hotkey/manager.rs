@@ -6,16 +6,28 @@ use std::sync::mpsc;
 use std::thread;
 use tauri::Emitter;
 
+use crate::config::HotkeyConfig;
+
 #[cfg(target_os = "windows")]
 use windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE,
 };
 
+/// What a registered hotkey does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    /// Hold to record: emits `hotkey-pressed` and `hotkey-released`.
+    Record,
+    /// Emits `pause-hotkey-pressed` to pause or resume the current recording.
+    Pause,
+}
+
 // Commands to send to the hotkey thread
 #[allow(dead_code)]
 enum HotkeyCommand {
-    Register(Modifiers, Code, mpsc::Sender<Result<(), String>>),
-    Unregister(mpsc::Sender<Result<(), String>>),
+    Register(HotkeyAction, Modifiers, Code, mpsc::Sender<Result<(), String>>),
+    // None unregisters every action
+    Unregister(Option<HotkeyAction>, mpsc::Sender<Result<(), String>>),
 }
 
 pub struct HotkeyManager {
@@ -41,7 +53,8 @@ impl HotkeyManager {
             };
 
             let event_receiver = GlobalHotKeyEvent::receiver();
-            let mut current_hotkey: Option<HotKey> = None;
+            // One hotkey per action
+            let mut registered: Vec<(HotkeyAction, HotKey)> = Vec::new();
 
             loop {
                 // Pump Windows messages (required for global hotkeys to work)
@@ -56,15 +69,27 @@ impl HotkeyManager {
 
                 // Check for hotkey events (non-blocking)
                 if let Ok(event) = event_receiver.try_recv() {
-                    match event.state {
-                        HotKeyState::Pressed => {
+                    let action = registered
+                        .iter()
+                        .find(|(_, hotkey)| hotkey.id() == event.id)
+                        .map(|(action, _)| *action);
+                    match (action, event.state) {
+                        (Some(HotkeyAction::Pause), HotKeyState::Pressed) => {
+                            println!(">>> PAUSE HOTKEY PRESSED! <<<");
+                            log::info!("Pause hotkey pressed");
+                            if let Err(e) = app_handle.emit("pause-hotkey-pressed", ()) {
+                                log::error!("Failed to emit pause-hotkey-pressed event: {}", e);
+                            }
+                        }
+                        (Some(HotkeyAction::Pause), HotKeyState::Released) => {}
+                        (_, HotKeyState::Pressed) => {
                             println!(">>> HOTKEY PRESSED! <<<");
                             log::info!("Hotkey pressed - start recording");
                             if let Err(e) = app_handle.emit("hotkey-pressed", ()) {
                                 log::error!("Failed to emit hotkey-pressed event: {}", e);
                             }
                         }
-                        HotKeyState::Released => {
+                        (_, HotKeyState::Released) => {
                             println!(">>> HOTKEY RELEASED! <<<");
                             log::info!("Hotkey released - stop recording");
                             if let Err(e) = app_handle.emit("hotkey-released", ()) {
@@ -76,9 +101,10 @@ impl HotkeyManager {
 
                 // Check for commands (non-blocking)
                 match rx.try_recv() {
-                    Ok(HotkeyCommand::Register(modifiers, key, response_tx)) => {
-                        // Unregister current hotkey if exists
-                        if let Some(hotkey) = current_hotkey.take() {
+                    Ok(HotkeyCommand::Register(action, modifiers, key, response_tx)) => {
+                        // Unregister the action's current hotkey if it exists
+                        if let Some(index) = registered.iter().position(|(a, _)| *a == action) {
+                            let (_, hotkey) = registered.remove(index);
                             if let Err(e) = manager.unregister(hotkey) {
                                 log::warn!("Failed to unregister previous hotkey: {}", e);
                             }
@@ -88,8 +114,8 @@ impl HotkeyManager {
                         let hotkey = HotKey::new(Some(modifiers), key);
                         match manager.register(hotkey) {
                             Ok(()) => {
-                                current_hotkey = Some(hotkey);
-                                log::info!("Hotkey registered: {:?} + {:?}", modifiers, key);
+                                registered.push((action, hotkey));
+                                log::info!("{:?} hotkey registered: {:?} + {:?}", action, modifiers, key);
                                 let _ = response_tx.send(Ok(()));
                             }
                             Err(e) => {
@@ -99,22 +125,18 @@ impl HotkeyManager {
                             }
                         }
                     }
-                    Ok(HotkeyCommand::Unregister(response_tx)) => {
-                        if let Some(hotkey) = current_hotkey.take() {
-                            match manager.unregister(hotkey) {
-                                Ok(()) => {
-                                    let _ = response_tx.send(Ok(()));
-                                }
-                                Err(e) => {
-                                    let _ = response_tx.send(Err(format!(
-                                        "Failed to unregister hotkey: {}",
-                                        e
-                                    )));
-                                }
+                    Ok(HotkeyCommand::Unregister(action, response_tx)) => {
+                        let mut result = Ok(());
+                        let (removed, kept) = registered
+                            .drain(..)
+                            .partition(|(a, _)| action.is_none_or(|action| *a == action));
+                        registered = kept;
+                        for (_, hotkey) in removed {
+                            if let Err(e) = manager.unregister(hotkey) {
+                                result = Err(format!("Failed to unregister hotkey: {}", e));
                             }
-                        } else {
-                            let _ = response_tx.send(Ok(()));
                         }
+                        let _ = response_tx.send(result);
                     }
                     Err(mpsc::TryRecvError::Disconnected) => {
                         // Channel closed, exit thread
@@ -133,10 +155,11 @@ impl HotkeyManager {
         Ok(Self { command_sender: tx })
     }
 
-    pub async fn register(&mut self, modifiers: Modifiers, key: Code) -> Result<(), String> {
+    /// Bind `action` to the key combination, replacing its previous binding.
+    pub async fn register(&mut self, action: HotkeyAction, modifiers: Modifiers, key: Code) -> Result<(), String> {
         let (response_tx, response_rx) = mpsc::channel();
         self.command_sender
-            .send(HotkeyCommand::Register(modifiers, key, response_tx))
+            .send(HotkeyCommand::Register(action, modifiers, key, response_tx))
             .map_err(|e| format!("Failed to send register command: {}", e))?;
 
         response_rx
@@ -146,9 +169,18 @@ impl HotkeyManager {
 
     #[allow(dead_code)]
     pub async fn unregister(&mut self) -> Result<(), String> {
+        self.send_unregister(None)
+    }
+
+    /// Remove the binding of `action`, if it has one.
+    pub async fn unregister_action(&mut self, action: HotkeyAction) -> Result<(), String> {
+        self.send_unregister(Some(action))
+    }
+
+    fn send_unregister(&self, action: Option<HotkeyAction>) -> Result<(), String> {
         let (response_tx, response_rx) = mpsc::channel();
         self.command_sender
-            .send(HotkeyCommand::Unregister(response_tx))
+            .send(HotkeyCommand::Unregister(action, response_tx))
             .map_err(|e| format!("Failed to send unregister command: {}", e))?;
 
         response_rx
@@ -157,6 +189,28 @@ impl HotkeyManager {
     }
 }
 
+/// Modifiers and key of a configured hotkey, or None if any part is unknown.
+pub fn parse_hotkey(config: &HotkeyConfig) -> Option<(Modifiers, Code)> {
+    let mut modifiers = parse_modifier(&config.modifier1)?;
+    if let Some(modifier2) = config.modifier2.as_deref().and_then(parse_modifier) {
+        modifiers |= modifier2;
+    }
+    Some((modifiers, parse_key(&config.key)?))
+}
+
+/// Modifiers and key of the pause hotkey, or None if it is unbound. Fails if
+/// it can't be parsed or is the same combination as the `record` hotkey.
+pub fn parse_pause_hotkey(record: &HotkeyConfig, pause: Option<&HotkeyConfig>) -> Result<Option<(Modifiers, Code)>, String> {
+    let Some(pause) = pause else {
+        return Ok(None);
+    };
+    let parsed = parse_hotkey(pause).ok_or_else(|| format!("Invalid pause hotkey: {:?}", pause))?;
+    if parse_hotkey(record) == Some(parsed) {
+        return Err("The pause hotkey must differ from the recording hotkey".to_string());
+    }
+    Ok(Some(parsed))
+}
+
 pub fn parse_modifier(modifier: &str) -> Option<Modifiers> {
     match modifier.to_lowercase().as_str() {
         "ctrl" | "control" => Some(Modifiers::CONTROL),
@@ -211,3 +265,51 @@ pub fn parse_key(key_str: &str) -> Option<Code> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkey(modifier1: &str, modifier2: Option<&str>, key: &str) -> HotkeyConfig {
+        HotkeyConfig {
+            modifier1: modifier1.to_string(),
+            modifier2: modifier2.map(str::to_string),
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn unbound_pause_hotkey() {
+        let record = hotkey("ctrl", Some("shift"), "space");
+        assert_eq!(parse_pause_hotkey(&record, None), Ok(None));
+    }
+
+    #[test]
+    fn pause_hotkey_differing_from_record() {
+        let record = hotkey("ctrl", Some("shift"), "space");
+        let pause = hotkey("ctrl", Some("shift"), "p");
+        assert_eq!(
+            parse_pause_hotkey(&record, Some(&pause)),
+            Ok(Some((Modifiers::CONTROL | Modifiers::SHIFT, Code::KeyP)))
+        );
+    }
+
+    #[test]
+    fn pause_hotkey_equal_to_record_is_rejected() {
+        let record = hotkey("ctrl", Some("shift"), "space");
+        for pause in [
+            hotkey("ctrl", Some("shift"), "space"),
+            // Same combination, spelled differently
+            hotkey("Shift", Some("Control"), "SPACE"),
+        ] {
+            assert!(parse_pause_hotkey(&record, Some(&pause)).is_err(), "{:?}", pause);
+        }
+    }
+
+    #[test]
+    fn invalid_pause_hotkey_is_rejected() {
+        let record = hotkey("ctrl", None, "f9");
+        assert!(parse_pause_hotkey(&record, Some(&hotkey("ctrl", None, "enter"))).is_err());
+        assert!(parse_pause_hotkey(&record, Some(&hotkey("hyper", None, "f10"))).is_err());
+    }
+}
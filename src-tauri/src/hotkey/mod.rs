@@ -1,2 +1,2 @@
 pub mod manager;
-pub use manager::{HotkeyManager, parse_key, parse_modifier};
+pub use manager::{HotkeyAction, HotkeyManager, parse_key, parse_modifier, parse_pause_hotkey};
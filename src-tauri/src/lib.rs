@@ -2,18 +2,26 @@ mod audio;
 mod azure;
 mod config;
 mod commands;
+mod headless;
 mod hotkey;
 mod input;
+mod transcription;
 
-use crate::audio::AudioRecorder;
+use crate::audio::{AudioRecorder, RecorderEvent, RecordingOptions};
 use crate::commands::AppState;
 use crate::config::store;
-use crate::hotkey::{parse_key, parse_modifier, HotkeyManager};
+use crate::hotkey::{parse_key, parse_modifier, parse_pause_hotkey, HotkeyAction, HotkeyManager};
 use crate::input::TextInjector;
 use std::sync::Arc;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 
+/// Record and transcribe without starting the UI (see `headless.rs`).
+pub fn run_headless(args: &[String]) -> i32 {
+    env_logger::init();
+    headless::run(args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::init();
@@ -25,16 +33,40 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
             // Initialize app state
-            let recorder = Arc::new(Mutex::new(
-                AudioRecorder::new()
-                    .unwrap_or_else(|e| {
-                        log::warn!("Audio recorder init failed (no mic?): {e}. Recording will be unavailable.");
-                        AudioRecorder::new_dummy()
-                    }),
-            ));
+            let mut recorder = AudioRecorder::new()
+                .unwrap_or_else(|e| {
+                    log::warn!("Audio recorder init failed (no mic?): {e}. Recording will be unavailable.");
+                    AudioRecorder::new_dummy()
+                });
+
+            // Forward recorder events to the frontend
+            let event_handle = app.handle().clone();
+            recorder.set_event_handler(Arc::new(move |event| {
+                let result = match event {
+                    RecorderEvent::LimitReached(limit) => {
+                        event_handle.emit("recording-limit-reached", limit)
+                    }
+                    RecorderEvent::Level(levels) => event_handle.emit("audio-level", levels),
+                    RecorderEvent::StreamError(error) => event_handle.emit("recording-error", error),
+                    RecorderEvent::Paused(state) => event_handle.emit("recording-paused", state),
+                    RecorderEvent::SilenceCountdown(countdown) => event_handle.emit("silence-countdown", countdown),
+                };
+                if let Err(e) = result {
+                    log::error!("Failed to emit recorder event: {}", e);
+                }
+            }));
+            if let Ok(config) = store::load_config(app.handle()) {
+                recorder.apply_warm_microphone(&RecordingOptions::from(&config));
+            }
+            let recorder = Arc::new(Mutex::new(recorder));
             let injector = Arc::new(Mutex::new(TextInjector::new()));
 
-            app.manage(AppState { recorder, injector });
+            app.manage(AppState {
+                recorder,
+                injector,
+                last_recording_path: Arc::new(Mutex::new(None)),
+                streaming: Arc::new(Mutex::new(None)),
+            });
 
             // Position main window
             if let Some(window) = app.get_webview_window("main") {
@@ -123,7 +155,7 @@ pub fn run() {
                                 let mut hotkey_manager = HotkeyManager::new(app_handle.clone())
                                     .expect("Failed to create hotkey manager");
 
-                                if let Err(e) = hotkey_manager.register(modifiers, key).await {
+                                if let Err(e) = hotkey_manager.register(HotkeyAction::Record, modifiers, key).await {
                                     println!("ERROR: Failed to register hotkey: {}", e);
                                 } else {
                                     println!(
@@ -133,6 +165,17 @@ pub fn run() {
                                     );
                                 }
 
+                                match parse_pause_hotkey(&config.hotkey, config.pause_hotkey.as_ref()) {
+                                    Ok(Some((modifiers, key))) => {
+                                        match hotkey_manager.register(HotkeyAction::Pause, modifiers, key).await {
+                                            Ok(()) => println!("SUCCESS: Pause hotkey registered: {:?} + {:?}", modifiers, key),
+                                            Err(e) => println!("ERROR: Failed to register pause hotkey: {}", e),
+                                        }
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => println!("ERROR: Pause hotkey not registered: {}", e),
+                                }
+
                                 // Keep hotkey manager alive
                                 app_handle.manage(Arc::new(Mutex::new(hotkey_manager)));
                             } else {
@@ -153,10 +196,16 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::get_config,
             commands::save_config_cmd,
+            commands::list_input_devices,
+            commands::list_loopback_devices,
             commands::start_recording,
             commands::stop_recording,
+            commands::pause_recording,
+            commands::resume_recording,
             commands::get_audio_level,
+            commands::get_capture_stats,
             commands::transcribe_and_insert,
+            commands::transcribe_file,
             commands::open_config_window,
             commands::save_history_item,
             commands::load_history,
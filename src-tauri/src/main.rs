@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `--headless <audio file>` runs the recording pipeline without the UI or a microphone
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--headless") {
+        std::process::exit(flux_voice_lib::run_headless(&args));
+    }
+
     flux_voice_lib::run()
 }
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use super::{Transcript, TranscriptionOptions, TranscriptionProvider};
+use crate::azure::speech;
+use crate::config::AppConfig;
+
+// 1 initial attempt + 1 retry
+const MAX_ATTEMPTS: u32 = 2;
+
+/// Azure AI Speech Fast Transcription (see `azure::speech`).
+pub struct AzureFastTranscription {
+    key: String,
+    region: String,
+}
+
+impl AzureFastTranscription {
+    pub fn from_config(config: &AppConfig) -> Result<Self, String> {
+        if config.azure.speech_key.is_empty() {
+            return Err("Azure Speech key not configured".to_string());
+        }
+        Ok(Self {
+            key: config.azure.speech_key.clone(),
+            region: config.azure.speech_region.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for AzureFastTranscription {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    async fn transcribe(&self, audio: Vec<u8>, options: &TranscriptionOptions) -> Result<Transcript, String> {
+        speech::transcribe_audio_with_retry(
+            audio,
+            &self.key,
+            &self.region,
+            &options.languages,
+            options.multilingual,
+            MAX_ATTEMPTS,
+        )
+        .await
+    }
+}
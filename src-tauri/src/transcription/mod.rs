@@ -0,0 +1,112 @@
+pub mod azure;
+pub mod openai;
+#[cfg(feature = "local-whisper")]
+pub mod whisper;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::config::AppConfig;
+
+/// What a provider recognized in a recording.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub language: Option<String>,  // Detected locale, if the engine reports one
+    pub duration_secs: Option<f64>,  // Audio duration as measured by the engine
+    pub segments: Vec<TranscriptSegment>,  // Timing detail, empty if the engine gives none
+}
+
+/// A phrase of the transcript with its position in the uploaded audio.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+    pub confidence: Option<f32>,  // 0.0 - 1.0
+    #[serde(default)]
+    pub words: Vec<TranscriptWord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    pub text: String,
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+    pub confidence: Option<f32>,  // Per-word if the engine reports it, else None
+}
+
+/// Settings every provider understands.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionOptions {
+    pub languages: Vec<String>,  // Candidate locales, e.g. "en-US"
+    pub multilingual: bool,  // Let the engine detect the language itself
+}
+
+impl TranscriptionOptions {
+    /// The single ISO 639-1 code Whisper-style engines take ("en" for "en-US"),
+    /// or `None` to let them detect the language.
+    pub fn language_code(&self) -> Option<String> {
+        if self.multilingual || self.languages.len() != 1 {
+            return None;
+        }
+        self.languages[0]
+            .split('-')
+            .next()
+            .filter(|code| !code.is_empty())
+            .map(|code| code.to_lowercase())
+    }
+}
+
+impl From<&AppConfig> for TranscriptionOptions {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            languages: config.language.speech_languages.clone(),
+            multilingual: config.language.multilingual,
+        }
+    }
+}
+
+/// A speech-to-text engine. Implementations take the recorder's encoded
+/// output (Ogg Opus or WAV) and handle their own retries and limits.
+#[async_trait]
+pub trait TranscriptionProvider: Send + Sync {
+    /// Short name for logs, matching the `provider` config value.
+    fn name(&self) -> &'static str;
+
+    async fn transcribe(&self, audio: Vec<u8>, options: &TranscriptionOptions) -> Result<Transcript, String>;
+}
+
+/// Run a transcription request up to `max_attempts` times, waiting 1 s, 2 s,
+/// 4 s... between attempts. Shared by the providers that call a web service.
+pub async fn with_retry<T, F, Fut>(max_attempts: u32, mut request: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    for attempt in 0..max_attempts {
+        match request().await {
+            Ok(result) => return Ok(result),
+            Err(e) if attempt < max_attempts - 1 => {
+                log::warn!("Transcription attempt {} failed: {}. Retrying...", attempt + 1, e);
+                tokio::time::sleep(tokio::time::Duration::from_secs(2_u64.pow(attempt))).await;
+            }
+            Err(e) => return Err(format!("Transcription failed after {} attempts: {}", max_attempts, e)),
+        }
+    }
+    Err("Unexpected error in retry logic".to_string())
+}
+
+/// Build the provider selected by `config.provider`.
+pub fn provider_from_config(config: &AppConfig) -> Result<Box<dyn TranscriptionProvider>, String> {
+    match config.provider.as_str() {
+        "azure" => Ok(Box::new(azure::AzureFastTranscription::from_config(config)?)),
+        "openai" => Ok(Box::new(openai::OpenAiTranscription::from_config(config)?)),
+        #[cfg(feature = "local-whisper")]
+        "whisper" => Ok(Box::new(whisper::LocalWhisper::from_config(config)?)),
+        #[cfg(not(feature = "local-whisper"))]
+        "whisper" => Err("This build has no local Whisper support (build with the `local-whisper` feature)".to_string()),
+        other => Err(format!("Unknown transcription provider '{}'", other)),
+    }
+}
@@ -0,0 +1,398 @@
+use async_trait::async_trait;
+use reqwest::{multipart, StatusCode};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{with_retry, Transcript, TranscriptSegment, TranscriptionOptions, TranscriptionProvider};
+use crate::audio::encoder::UploadFormat;
+use crate::azure::get_http_client;
+use crate::config::AppConfig;
+
+// 1 initial attempt + 1 retry
+const MAX_ATTEMPTS: u32 = 2;
+
+// Requested as verbose_json, which also carries `language`, `duration` and
+// `segments`; servers that only support plain json return just `text`
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+    language: Option<String>,
+    duration: Option<f64>,
+    #[serde(default)]
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Segment {
+    text: String,
+    start: f64, // Seconds
+    end: f64,
+}
+
+impl Segment {
+    fn to_segment(&self) -> Option<TranscriptSegment> {
+        let text = self.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let offset_ms = (self.start.max(0.0) * 1000.0).round() as u64;
+        let end_ms = (self.end.max(0.0) * 1000.0).round() as u64;
+        Some(TranscriptSegment {
+            text: text.to_string(),
+            offset_ms,
+            duration_ms: end_ms.saturating_sub(offset_ms),
+            ..Default::default()
+        })
+    }
+}
+
+/// Any server speaking the OpenAI `audio/transcriptions` multipart API:
+/// OpenAI itself, Azure OpenAI Whisper deployments or a self-hosted
+/// faster-whisper server.
+pub struct OpenAiTranscription {
+    url: String,
+    model: String,
+    api_key: String,
+    language: Option<String>,
+    azure: bool,
+    // Set once the server rejected verbose_json
+    plain_json_only: AtomicBool,
+}
+
+impl OpenAiTranscription {
+    pub fn from_config(config: &AppConfig) -> Result<Self, String> {
+        let settings = &config.openai_transcription;
+        let base_url = settings.base_url.trim();
+        if base_url.is_empty() {
+            return Err("OpenAI transcription base URL not configured".to_string());
+        }
+
+        let language = settings.language.trim();
+        Ok(Self {
+            url: transcriptions_url(base_url),
+            model: settings.model.trim().to_string(),
+            api_key: settings.api_key.trim().to_string(),
+            language: (!language.is_empty()).then(|| language.to_string()),
+            // Azure OpenAI authenticates with an `api-key` header instead of a bearer token
+            azure: base_url.contains(".azure.com"),
+            plain_json_only: AtomicBool::new(false),
+        })
+    }
+
+    /// Post the audio, asking for the given `response_format`.
+    async fn send(&self, audio: &[u8], language: Option<&str>, response_format: &str) -> Result<reqwest::Response, String> {
+        let format = UploadFormat::detect(audio);
+        log::info!(
+            "Sending {} bytes of {:?} audio to {} (model: {}, language: {}, format: {})",
+            audio.len(),
+            format,
+            self.url,
+            self.model,
+            language.unwrap_or("auto"),
+            response_format
+        );
+
+        let audio_part = multipart::Part::bytes(audio.to_vec())
+            .file_name(format.file_name())
+            .mime_str(format.mime_type())
+            .map_err(|e| format!("Failed to create audio part: {}", e))?;
+
+        let mut form = multipart::Form::new()
+            .part("file", audio_part)
+            .text("response_format", response_format.to_string());
+        // Azure OpenAI takes the model from the deployment in the URL
+        if !self.model.is_empty() {
+            form = form.text("model", self.model.clone());
+        }
+        if let Some(language) = language {
+            form = form.text("language", language.to_string());
+        }
+
+        let mut request = get_http_client().post(&self.url).multipart(form);
+        if !self.api_key.is_empty() {
+            request = if self.azure {
+                request.header("api-key", &self.api_key)
+            } else {
+                request.bearer_auth(&self.api_key)
+            };
+        }
+
+        request.send().await.map_err(|e| format!("Request failed: {}", e))
+    }
+
+    async fn transcribe_once(&self, audio: &[u8], language: Option<&str>) -> Result<Transcript, String> {
+        let plain_json_only = self.plain_json_only.load(Ordering::Relaxed);
+        let response_format = if plain_json_only { "json" } else { "verbose_json" };
+        let mut response = self.send(audio, language, response_format).await?;
+
+        if !plain_json_only && response.status() == StatusCode::BAD_REQUEST {
+            // Some servers only know plain json. If that works, stick with it
+            let error_body = response.text().await.unwrap_or_default();
+            log::warn!("verbose_json rejected ({}), falling back to json", error_body);
+            response = self.send(audio, language, "json").await?;
+            if response.status().is_success() {
+                self.plain_json_only.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(format!("API error ({}): {}", status, error_body));
+        }
+
+        let result: TranscriptionResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Parse error: {}", e))?;
+
+        let text = result.text.trim().to_string();
+        if text.is_empty() {
+            return Err("No transcription text in response".to_string());
+        }
+
+        log::info!("Transcription successful");
+        Ok(Transcript {
+            text,
+            language: result.language.or_else(|| language.map(str::to_string)),
+            duration_secs: result.duration,
+            segments: result.segments.iter().filter_map(Segment::to_segment).collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for OpenAiTranscription {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn transcribe(&self, audio: Vec<u8>, options: &TranscriptionOptions) -> Result<Transcript, String> {
+        // A configured hint wins over the speech languages
+        let language = self.language.clone().or_else(|| options.language_code());
+
+        with_retry(MAX_ATTEMPTS, || self.transcribe_once(&audio, language.as_deref())).await
+    }
+}
+
+/// Append `/audio/transcriptions` to the base URL, keeping any query string
+/// (Azure OpenAI needs `?api-version=...`).
+fn transcriptions_url(base_url: &str) -> String {
+    let (path, query) = match base_url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (base_url, None),
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix("/audio/transcriptions").unwrap_or(path);
+    match query {
+        Some(query) => format!("{}/audio/transcriptions?{}", path, query),
+        None => format!("{}/audio/transcriptions", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const VERBOSE_RESPONSE: &str = r#"{"text":" Hello there. How are you?","language":"english","duration":2.5,
+        "segments":[{"id":0,"start":0.0,"end":1.2,"text":" Hello there."},{"id":1,"start":1.2,"end":2.5,"text":" How are you?"}]}"#;
+    const PLAIN_RESPONSE: &str = r#"{"text":"Hello there."}"#;
+
+    /// Serve one canned (status, body) response per request, in order, and
+    /// record each request's headers and body.
+    async fn serve(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                recorded.lock().unwrap().push(request);
+                let response = format!(
+                    "HTTP/1.1 {} Test\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            }
+        });
+        (base_url, requests)
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&data);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if data.len() >= header_end + 4 + content_length {
+                    return text.into_owned();
+                }
+            }
+            if read == 0 {
+                return String::from_utf8_lossy(&data).into_owned();
+            }
+        }
+    }
+
+    fn provider(base_url: &str, api_key: &str, language: &str) -> OpenAiTranscription {
+        let mut config = AppConfig::default();
+        config.openai_transcription.base_url = base_url.to_string();
+        config.openai_transcription.api_key = api_key.to_string();
+        config.openai_transcription.language = language.to_string();
+        OpenAiTranscription::from_config(&config).unwrap()
+    }
+
+    fn options(languages: &[&str], multilingual: bool) -> TranscriptionOptions {
+        TranscriptionOptions {
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+            multilingual,
+        }
+    }
+
+    fn response_format(request: &str) -> &str {
+        let field = request.split("name=\"response_format\"\r\n\r\n").nth(1).unwrap();
+        field.split("\r\n").next().unwrap()
+    }
+
+    #[test]
+    fn transcriptions_url_appends_the_path() {
+        assert_eq!(transcriptions_url("https://api.openai.com/v1"), "https://api.openai.com/v1/audio/transcriptions");
+        assert_eq!(transcriptions_url("https://api.openai.com/v1/"), "https://api.openai.com/v1/audio/transcriptions");
+        assert_eq!(
+            transcriptions_url("http://localhost:8000/v1/audio/transcriptions"),
+            "http://localhost:8000/v1/audio/transcriptions"
+        );
+    }
+
+    #[test]
+    fn transcriptions_url_keeps_the_query_string() {
+        assert_eq!(
+            transcriptions_url("https://res.openai.azure.com/openai/deployments/whisper/?api-version=2024-06-01"),
+            "https://res.openai.azure.com/openai/deployments/whisper/audio/transcriptions?api-version=2024-06-01"
+        );
+    }
+
+    #[test]
+    fn detects_azure_from_the_host() {
+        assert!(provider("https://res.openai.azure.com/openai/deployments/whisper?api-version=2024-06-01", "", "").azure);
+        assert!(!provider("https://api.openai.com/v1", "", "").azure);
+        assert!(!provider("http://localhost:8000/v1", "", "").azure);
+    }
+
+    #[test]
+    fn language_code_from_speech_languages() {
+        assert_eq!(options(&["en-US"], false).language_code().as_deref(), Some("en"));
+        assert_eq!(options(&["ZH-cn"], false).language_code().as_deref(), Some("zh"));
+        assert_eq!(options(&["de"], false).language_code().as_deref(), Some("de"));
+        // Several candidates, or none, leave detection to the engine
+        assert_eq!(options(&["en-US", "de-DE"], false).language_code(), None);
+        assert_eq!(options(&[], false).language_code(), None);
+        assert_eq!(options(&["en-US"], true).language_code(), None);
+    }
+
+    #[tokio::test]
+    async fn sends_the_language_and_maps_segments() {
+        let (base_url, requests) = serve(vec![(200, VERBOSE_RESPONSE)]).await;
+        let provider = provider(&base_url, "sk-test", "");
+
+        let transcript = provider.transcribe(b"RIFF".to_vec(), &options(&["en-GB"], false)).await.unwrap();
+        assert_eq!(transcript.text, "Hello there. How are you?");
+        assert_eq!(transcript.language.as_deref(), Some("english"));
+        assert_eq!(transcript.duration_secs, Some(2.5));
+        let segments: Vec<_> = transcript.segments.iter().map(|s| (s.text.as_str(), s.offset_ms, s.duration_ms)).collect();
+        assert_eq!(segments, [("Hello there.", 0, 1200), ("How are you?", 1200, 1300)]);
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /v1/audio/transcriptions "));
+        assert!(requests[0].to_lowercase().contains("authorization: bearer sk-test"));
+        assert!(requests[0].contains("name=\"language\"\r\n\r\nen\r\n"));
+        assert_eq!(response_format(&requests[0]), "verbose_json");
+    }
+
+    #[tokio::test]
+    async fn configured_language_wins() {
+        let (base_url, requests) = serve(vec![(200, PLAIN_RESPONSE)]).await;
+        let provider = provider(&base_url, "", "fr");
+
+        let transcript = provider.transcribe(b"RIFF".to_vec(), &options(&["en-US"], false)).await.unwrap();
+        // Plain json has no language, so the hint that was sent is reported
+        assert_eq!(transcript.language.as_deref(), Some("fr"));
+        assert!(transcript.segments.is_empty());
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("name=\"language\"\r\n\r\nfr\r\n"));
+        assert!(!requests[0].to_lowercase().contains("authorization:"));
+    }
+
+    #[tokio::test]
+    async fn azure_authenticates_with_api_key_header() {
+        let (base_url, requests) = serve(vec![(200, PLAIN_RESPONSE)]).await;
+        let mut provider = provider(&base_url, "azure-key", "");
+        // A local server can't be on .azure.com
+        provider.azure = true;
+
+        provider.transcribe(b"RIFF".to_vec(), &options(&[], true)).await.unwrap();
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.contains("api-key: azure-key"));
+        assert!(!request.contains("authorization:"));
+        assert!(!request.contains("name=\"language\""));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_json_after_a_400_and_sticks_with_it() {
+        let (base_url, requests) = serve(vec![
+            (400, r#"{"error":"unsupported response_format"}"#),
+            (200, PLAIN_RESPONSE),
+            (200, PLAIN_RESPONSE),
+        ])
+        .await;
+        let provider = provider(&base_url, "", "");
+
+        let transcript = provider.transcribe(b"RIFF".to_vec(), &options(&[], true)).await.unwrap();
+        assert_eq!(transcript.text, "Hello there.");
+        assert!(provider.plain_json_only.load(Ordering::Relaxed));
+
+        // The next recording goes straight to json
+        provider.transcribe(b"RIFF".to_vec(), &options(&[], true)).await.unwrap();
+        let formats: Vec<_> = requests.lock().unwrap().iter().map(|r| response_format(r).to_string()).collect();
+        assert_eq!(formats, ["verbose_json", "json", "json"]);
+    }
+
+    #[tokio::test]
+    async fn failed_fallback_does_not_stick() {
+        let (base_url, requests) = serve(vec![
+            (400, r#"{"error":"bad audio"}"#),
+            (400, r#"{"error":"bad audio"}"#),
+            (200, VERBOSE_RESPONSE),
+        ])
+        .await;
+        let provider = provider(&base_url, "", "");
+
+        // Rejected both ways, so the next request asks for verbose_json again
+        let error = provider.transcribe_once(b"RIFF", None).await.unwrap_err();
+        assert!(error.starts_with("API error (400"), "{}", error);
+        assert!(!provider.plain_json_only.load(Ordering::Relaxed));
+
+        let transcript = provider.transcribe_once(b"RIFF", None).await.unwrap();
+        assert_eq!(transcript.segments.len(), 2);
+        let formats: Vec<_> = requests.lock().unwrap().iter().map(|r| response_format(r).to_string()).collect();
+        assert_eq!(formats, ["verbose_json", "json", "verbose_json"]);
+    }
+}
@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+use super::{Transcript, TranscriptSegment, TranscriptionOptions, TranscriptionProvider};
+use crate::audio::decode;
+use crate::audio::downmix::{ChannelMix, Downmixer};
+use crate::audio::resample::Resampler;
+use crate::audio::TARGET_SAMPLE_RATE;
+use crate::config::AppConfig;
+
+// Model path and the context loaded from it
+type LoadedModel = (String, Arc<WhisperContext>);
+
+// Loading a model takes seconds and hundreds of MB, so the last one stays loaded
+static LOADED_MODEL: OnceLock<Mutex<Option<LoadedModel>>> = OnceLock::new();
+
+/// whisper.cpp running on the CPU with a user-supplied ggml model file. Works
+/// without a network connection.
+pub struct LocalWhisper {
+    model_path: String,
+    threads: u32,
+}
+
+impl LocalWhisper {
+    pub fn from_config(config: &AppConfig) -> Result<Self, String> {
+        let model_path = config.whisper.model_path.trim();
+        if model_path.is_empty() {
+            return Err("Whisper model file not configured".to_string());
+        }
+        if !Path::new(model_path).is_file() {
+            return Err(format!("Whisper model file not found: {}", model_path));
+        }
+        Ok(Self {
+            model_path: model_path.to_string(),
+            threads: config.whisper.threads,
+        })
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for LocalWhisper {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    async fn transcribe(&self, audio: Vec<u8>, options: &TranscriptionOptions) -> Result<Transcript, String> {
+        let model_path = self.model_path.clone();
+        let threads = self.threads;
+        let language = options.language_code();
+
+        // Inference is CPU-bound and takes about as long as the recording, keep it off the runtime
+        tokio::task::spawn_blocking(move || {
+            let samples = to_whisper_samples(audio)?;
+            let context = load_model(&model_path)?;
+            run_whisper(&context, &samples, language.as_deref(), threads)
+        })
+        .await
+        .map_err(|e| format!("Whisper task failed: {}", e))?
+    }
+}
+
+/// Decode the recorder's output to 16 kHz mono f32, the only input whisper.cpp accepts.
+fn to_whisper_samples(audio: Vec<u8>) -> Result<Vec<f32>, String> {
+    let decoded = decode::decode_bytes(audio, None)?;
+
+    let mut mono = Vec::with_capacity(decoded.samples.len() / decoded.channels.max(1) as usize);
+    Downmixer::new(ChannelMix::Average, decoded.channels, decoded.sample_rate).process(&decoded.samples, &mut mono);
+    if decoded.sample_rate == TARGET_SAMPLE_RATE {
+        return Ok(mono);
+    }
+
+    let mut samples = Vec::new();
+    let mut resampler = Resampler::new(decoded.sample_rate, TARGET_SAMPLE_RATE);
+    resampler.process(&mono, &mut samples);
+    resampler.flush(&mut samples);
+    Ok(samples)
+}
+
+fn load_model(model_path: &str) -> Result<Arc<WhisperContext>, String> {
+    let mut loaded = LOADED_MODEL
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .map_err(|_| "Whisper model lock poisoned".to_string())?;
+
+    if let Some((path, context)) = loaded.as_ref() {
+        if path == model_path {
+            return Ok(Arc::clone(context));
+        }
+    }
+
+    log::info!("Loading Whisper model {}", model_path);
+    println!(">>> Loading Whisper model {}", model_path);
+    let context = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+        .map_err(|e| format!("Failed to load Whisper model {}: {}", model_path, e))?;
+    let context = Arc::new(context);
+    *loaded = Some((model_path.to_string(), Arc::clone(&context)));
+    Ok(context)
+}
+
+fn run_whisper(
+    context: &WhisperContext,
+    samples: &[f32],
+    language: Option<&str>,
+    threads: u32,
+) -> Result<Transcript, String> {
+    let threads = if threads == 0 {
+        std::thread::available_parallelism().map_or(4, |n| n.get().min(8))
+    } else {
+        threads as usize
+    };
+
+    log::info!(
+        "Transcribing {:.1}s with local Whisper ({} threads, language: {})",
+        samples.len() as f64 / TARGET_SAMPLE_RATE as f64,
+        threads,
+        language.unwrap_or("auto")
+    );
+    println!(">>> Transcribing with local Whisper ({} threads, language: {})", threads, language.unwrap_or("auto"));
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_n_threads(threads as i32);
+    params.set_language(Some(language.unwrap_or("auto")));
+    params.set_translate(false);
+    params.set_no_context(true);
+    params.set_suppress_blank(true);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    let mut state = context
+        .create_state()
+        .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+    state
+        .full(params, samples)
+        .map_err(|e| format!("Whisper transcription failed: {}", e))?;
+
+    let segments = state
+        .full_n_segments()
+        .map_err(|e| format!("Failed to read Whisper segments: {}", e))?;
+    let mut text = String::new();
+    let mut timed_segments = Vec::new();
+    for segment in 0..segments {
+        let read_error = |e| format!("Failed to read Whisper segment {}: {}", segment, e);
+        let segment_text = state.full_get_segment_text(segment).map_err(read_error)?;
+        // Segment times are in 10 ms units
+        let start = state.full_get_segment_t0(segment).map_err(read_error)?.max(0) as u64 * 10;
+        let end = state.full_get_segment_t1(segment).map_err(read_error)?.max(0) as u64 * 10;
+        text.push_str(&segment_text);
+        timed_segments.push(TranscriptSegment {
+            text: segment_text.trim().to_string(),
+            offset_ms: start,
+            duration_ms: end.saturating_sub(start),
+            ..Default::default()
+        });
+    }
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("No transcription text in response".to_string());
+    }
+
+    let language = match language {
+        Some(language) => Some(language.to_string()),
+        None => state
+            .full_lang_id_from_state()
+            .ok()
+            .and_then(whisper_rs::get_lang_str)
+            .map(str::to_string),
+    };
+    log::info!("Transcription successful");
+    Ok(Transcript {
+        text,
+        language,
+        duration_secs: Some(samples.len() as f64 / TARGET_SAMPLE_RATE as f64),
+        segments: timed_segments,
+    })
+}
@@ -1,4 +1,6 @@
 import React, { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
 import { Settings, Clock, BarChart3 } from 'lucide-react';
 import { Button } from '../common/Button';
 import { Input } from '../common/Input';
@@ -7,16 +9,28 @@ import { useConfig } from '../../hooks/useConfig';
 import { TranscriptionHistory } from './TranscriptionHistory';
 import { UsageStats } from './UsageStats';
 import { LanguageSelector } from './LanguageSelector';
-import type { AppConfig } from '../../types/config';
+import type { AppConfig, InputDeviceInfo } from '../../types/config';
 
 type TabType = 'settings' | 'history' | 'stats';
 
+// Names the backend's hotkey parser understands
+const HOTKEY_MODIFIERS = ['Ctrl', 'Alt', 'Shift', 'Win'];
+const HOTKEY_KEYS = [
+  ...Array.from({ length: 12 }, (_, i) => `F${i + 1}`),
+  ...'ABCDEFGHIJKLMNOPQRSTUVWXYZ'.split(''),
+  'Space',
+];
+// Suggested when the pause hotkey is switched on; it is unbound by default
+const DEFAULT_PAUSE_HOTKEY = { modifier1: 'Ctrl', modifier2: 'Alt', key: 'P' };
+
 export const ConfigPage: React.FC = () => {
   const { config, saveConfig } = useConfig();
   const [localConfig, setLocalConfig] = useState<AppConfig | null>(null);
   const [isSaving, setIsSaving] = useState(false);
   const [saveMessage, setSaveMessage] = useState('');
   const [activeTab, setActiveTab] = useState<TabType>('settings');
+  const [inputDevices, setInputDevices] = useState<InputDeviceInfo[]>([]);
+  const [loopbackDevices, setLoopbackDevices] = useState<InputDeviceInfo[]>([]);
 
   useEffect(() => {
     document.body.classList.add('config-page');
@@ -31,6 +45,36 @@ export const ConfigPage: React.FC = () => {
     }
   }, [config]);
 
+  useEffect(() => {
+    invoke<InputDeviceInfo[]>('list_input_devices')
+      .then(setInputDevices)
+      .catch((err) => console.error('Failed to list input devices:', err));
+    invoke<InputDeviceInfo[]>('list_loopback_devices')
+      .then(setLoopbackDevices)
+      .catch((err) => console.error('Failed to list loopback devices:', err));
+  }, []);
+
+  const captureSource = localConfig?.audio?.captureSource ?? 'microphone';
+
+  // Device the channel setting applies to: the selected one, or the system default
+  const selectedDevice = inputDevices.find((d) =>
+    localConfig?.audio?.inputDevice ? d.name === localConfig.audio.inputDevice : d.is_default
+  );
+
+  const handleBrowseModel = async () => {
+    if (!localConfig) return;
+    try {
+      const path = await open({
+        multiple: false,
+        filters: [{ name: 'Whisper model', extensions: ['bin'] }],
+      });
+      if (!path) return;
+      setLocalConfig({ ...localConfig, whisper: { ...localConfig.whisper, modelPath: path } });
+    } catch (err) {
+      console.error('Failed to pick Whisper model:', err);
+    }
+  };
+
   const handleSave = async () => {
     if (!localConfig) return;
 
@@ -101,6 +145,167 @@ export const ConfigPage: React.FC = () => {
         {activeTab === 'settings' && (
           <div className="flex-1 overflow-y-auto min-h-0">
             <div className="space-y-6">
+            {/* Transcription Engine */}
+            <div className="bg-white dark:bg-gray-800 rounded-lg shadow" style={{ padding: '24px 32px' }}>
+              <h2 className="text-xl font-semibold mb-4 text-gray-900 dark:text-white">Transcription Engine</h2>
+              <div className="space-y-4">
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Engine
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Azure Speech, any OpenAI-compatible server, or Whisper on this computer (offline)
+                    </p>
+                  </div>
+                  <select
+                    value={localConfig.provider}
+                    onChange={(e) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        provider: e.target.value as AppConfig['provider'],
+                      })
+                    }
+                    className="px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                  >
+                    <option value="azure">Azure Speech</option>
+                    <option value="openai">OpenAI-compatible</option>
+                    <option value="whisper">Local Whisper</option>
+                  </select>
+                </div>
+
+                {localConfig.provider === 'openai' && (
+                  <>
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Base URL
+                      </label>
+                      <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                        OpenAI, an Azure OpenAI Whisper deployment or a self-hosted server
+                      </p>
+                      <Input
+                        type="text"
+                        value={localConfig.openaiTranscription.baseUrl}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            openaiTranscription: { ...localConfig.openaiTranscription, baseUrl: e.target.value },
+                          })
+                        }
+                        placeholder="https://api.openai.com/v1"
+                      />
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Model
+                      </label>
+                      <Input
+                        type="text"
+                        value={localConfig.openaiTranscription.model}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            openaiTranscription: { ...localConfig.openaiTranscription, model: e.target.value },
+                          })
+                        }
+                        placeholder="e.g., whisper-1 (leave empty for Azure OpenAI)"
+                      />
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        API Key
+                      </label>
+                      <Input
+                        type="password"
+                        value={localConfig.openaiTranscription.apiKey}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            openaiTranscription: { ...localConfig.openaiTranscription, apiKey: e.target.value },
+                          })
+                        }
+                        placeholder="Optional for self-hosted servers"
+                      />
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Language Hint
+                      </label>
+                      <Input
+                        type="text"
+                        value={localConfig.openaiTranscription.language}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            openaiTranscription: { ...localConfig.openaiTranscription, language: e.target.value },
+                          })
+                        }
+                        placeholder="e.g., en (empty uses Speech Languages)"
+                      />
+                    </div>
+                  </>
+                )}
+
+                {localConfig.provider === 'whisper' && (
+                  <>
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Whisper Model File
+                      </label>
+                      <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                        A whisper.cpp ggml model, e.g. ggml-base.en.bin
+                      </p>
+                      <div className="flex gap-2">
+                        <Input
+                          type="text"
+                          value={localConfig.whisper.modelPath}
+                          onChange={(e) =>
+                            setLocalConfig({
+                              ...localConfig,
+                              whisper: { ...localConfig.whisper, modelPath: e.target.value },
+                            })
+                          }
+                          placeholder="Path to a .bin model file"
+                        />
+                        <Button variant="secondary" onClick={handleBrowseModel}>
+                          Browse
+                        </Button>
+                      </div>
+                    </div>
+
+                    <div className="flex items-center justify-between">
+                      <div>
+                        <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                          CPU Threads
+                        </label>
+                        <p className="text-sm text-gray-500 dark:text-gray-400">
+                          More threads transcribe faster but leave less CPU for other apps
+                        </p>
+                      </div>
+                      <select
+                        value={localConfig.whisper.threads}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            whisper: { ...localConfig.whisper, threads: Number(e.target.value) },
+                          })
+                        }
+                        className="px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value={0}>Automatic</option>
+                        <option value={2}>2</option>
+                        <option value={4}>4</option>
+                        <option value={8}>8</option>
+                      </select>
+                    </div>
+                  </>
+                )}
+              </div>
+            </div>
+
             {/* Azure Settings */}
             <div className="bg-white dark:bg-gray-800 rounded-lg shadow" style={{ padding: '24px 32px' }}>
               <h2 className="text-xl font-semibold mb-4 text-gray-900 dark:text-white">Azure Configuration</h2>
@@ -139,6 +344,28 @@ export const ConfigPage: React.FC = () => {
                   />
                 </div>
 
+                {localConfig.provider === 'azure' && (
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                        Streaming Recognition
+                      </label>
+                      <p className="text-sm text-gray-500 dark:text-gray-400">
+                        Show text while you speak. Recognizes the first speech language only.
+                      </p>
+                    </div>
+                    <Toggle
+                      checked={localConfig.azure.streaming}
+                      onChange={(checked) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          azure: { ...localConfig.azure, streaming: checked },
+                        })
+                      }
+                    />
+                  </div>
+                )}
+
                 <div>
                   <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
                     OpenAI Endpoint
@@ -289,6 +516,554 @@ export const ConfigPage: React.FC = () => {
                   />
                 </div>
 
+                <div>
+                  <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                    Capture Source
+                  </label>
+                  <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                    Record what the computer plays to transcribe calls and videos
+                  </p>
+                  <select
+                    value={captureSource}
+                    onChange={(e) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: {
+                          ...localConfig.audio,
+                          captureSource: e.target.value as AppConfig['audio']['captureSource'],
+                        },
+                      })
+                    }
+                    className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                  >
+                    <option value="microphone">Microphone</option>
+                    <option value="system">System audio</option>
+                    <option value="both">Microphone + system audio</option>
+                  </select>
+                </div>
+
+                {captureSource !== 'microphone' && (
+                  <div>
+                    <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                      System Audio Device
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                      {loopbackDevices.length > 0
+                        ? 'The output to record. On Linux these are the monitor sources of your sound server'
+                        : 'No loopback device found. On Linux enable a PulseAudio/PipeWire monitor source; on macOS install a loopback driver such as BlackHole'}
+                    </p>
+                    <select
+                      value={localConfig.audio?.loopbackDevice ?? ''}
+                      onChange={(e) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          audio: { ...localConfig.audio, loopbackDevice: e.target.value || null },
+                        })
+                      }
+                      className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                    >
+                      <option value="">Default</option>
+                      {loopbackDevices.map((device) => (
+                        <option key={device.name} value={device.name}>
+                          {device.name}{device.is_default ? ' (default)' : ''}
+                        </option>
+                      ))}
+                      {localConfig.audio?.loopbackDevice &&
+                        !loopbackDevices.some((d) => d.name === localConfig.audio.loopbackDevice) && (
+                          <option value={localConfig.audio.loopbackDevice}>
+                            {localConfig.audio.loopbackDevice} (unavailable)
+                          </option>
+                        )}
+                    </select>
+                  </div>
+                )}
+
+                <div>
+                  <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                    Microphone
+                  </label>
+                  <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                    Falls back to the system default if the selected device is unavailable
+                  </p>
+                  <select
+                    value={localConfig.audio?.inputDevice ?? ''}
+                    onChange={(e) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, inputDevice: e.target.value || null },
+                      })
+                    }
+                    className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                  >
+                    <option value="">System Default</option>
+                    {inputDevices.map((device) => (
+                      <option key={device.name} value={device.name}>
+                        {device.name}{device.is_default ? ' (default)' : ''}
+                      </option>
+                    ))}
+                    {localConfig.audio?.inputDevice &&
+                      !inputDevices.some((d) => d.name === localConfig.audio.inputDevice) && (
+                        <option value={localConfig.audio.inputDevice}>
+                          {localConfig.audio.inputDevice} (unavailable)
+                        </option>
+                      )}
+                  </select>
+                </div>
+
+                {selectedDevice && selectedDevice.channels > 1 && (
+                  <div>
+                    <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                      Input Channel
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                      {selectedDevice.name} has {selectedDevice.channels} channels. Pick the one your mic is on
+                      if the others are unused
+                    </p>
+                    <select
+                      value={localConfig.audio?.channelModes?.[selectedDevice.name] ?? 'average'}
+                      onChange={(e) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          audio: {
+                            ...localConfig.audio,
+                            channelModes: {
+                              ...localConfig.audio?.channelModes,
+                              [selectedDevice.name]: e.target.value,
+                            },
+                          },
+                        })
+                      }
+                      className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                    >
+                      <option value="average">Average all channels</option>
+                      <option value="loudest">Loudest channel</option>
+                      {Array.from({ length: selectedDevice.channels }, (_, i) => (
+                        <option key={i} value={`channel-${i + 1}`}>
+                          Channel {i + 1} only
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                )}
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Reconnect on Disconnect
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Keep recording on the system default microphone if the current one is unplugged
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.reconnectOnDisconnect ?? true}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, reconnectOnDisconnect: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Trim Silence
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Cut silence before and after speech; recordings without speech are not sent
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.trimSilence ?? true}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, trimSilence: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Warm Microphone
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Keep the microphone open so the first word isn't cut off. Your system will show the microphone as in use while FluxVoice runs
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.warmMicrophone ?? false}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, warmMicrophone: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                {localConfig.audio?.warmMicrophone && (
+                  <div>
+                    <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                      Pre-Roll
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                      Audio from just before the hotkey press that is included in the recording
+                    </p>
+                    <select
+                      value={localConfig.audio.preRollMs ?? 500}
+                      onChange={(e) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          audio: { ...localConfig.audio, preRollMs: Number(e.target.value) },
+                        })
+                      }
+                      className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                    >
+                      <option value={250}>250 ms</option>
+                      <option value={500}>500 ms</option>
+                      <option value={1000}>1 second</option>
+                    </select>
+                  </div>
+                )}
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Automatic Gain
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Boost quiet speech and tame loud input before transcription
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.autoGain ?? true}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, autoGain: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Noise Suppression
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Reduce steady background noise (fans, office chatter) before transcription
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.noiseSuppression ?? false}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, noiseSuppression: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                {localConfig.audio?.noiseSuppression && (
+                  <div>
+                    <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                      Suppression Strength: {Math.round((localConfig.audio.noiseSuppressionStrength ?? 0.5) * 100)}%
+                    </label>
+                    <input
+                      type="range"
+                      min={0}
+                      max={1}
+                      step={0.05}
+                      value={localConfig.audio.noiseSuppressionStrength ?? 0.5}
+                      onChange={(e) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          audio: { ...localConfig.audio, noiseSuppressionStrength: Number(e.target.value) },
+                        })
+                      }
+                      className="w-full accent-amber-500"
+                    />
+                  </div>
+                )}
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Hands-Free Mode
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Press the hotkey once to start; recording stops by itself when you stop talking
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.handsFree ?? false}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, handsFree: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                {localConfig.audio?.handsFree && (
+                  <div className="grid grid-cols-2 gap-4">
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Stop After Silence
+                      </label>
+                      <select
+                        value={localConfig.audio.silenceTimeoutMs ?? 1500}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            audio: { ...localConfig.audio, silenceTimeoutMs: Number(e.target.value) },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value={1000}>1 second</option>
+                        <option value={1500}>1.5 seconds</option>
+                        <option value={2000}>2 seconds</option>
+                        <option value={3000}>3 seconds</option>
+                        <option value={5000}>5 seconds</option>
+                      </select>
+                    </div>
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Hands-Free Maximum
+                      </label>
+                      <select
+                        value={localConfig.audio.handsFreeMaxSecs ?? 120}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            audio: { ...localConfig.audio, handsFreeMaxSecs: Number(e.target.value) },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value={30}>30 seconds</option>
+                        <option value={60}>1 minute</option>
+                        <option value={120}>2 minutes</option>
+                        <option value={300}>5 minutes</option>
+                      </select>
+                    </div>
+                  </div>
+                )}
+
+                <div>
+                  <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                    Maximum Recording Length
+                  </label>
+                  <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                    Recording stops and is transcribed automatically after this long
+                  </p>
+                  <select
+                    value={localConfig.audio?.maxRecordingSecs ?? 600}
+                    onChange={(e) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, maxRecordingSecs: Number(e.target.value) },
+                      })
+                    }
+                    className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                  >
+                    <option value={60}>1 minute</option>
+                    <option value={300}>5 minutes</option>
+                    <option value={600}>10 minutes</option>
+                    <option value={1800}>30 minutes</option>
+                    <option value={3600}>1 hour</option>
+                    <option value={7200}>2 hours</option>
+                  </select>
+                </div>
+
+                <div className="flex items-center justify-between">
+                  <div>
+                    <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                      Keep Lossless Copy
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400">
+                      Also save each recording as a 16 kHz WAV file, linked from the history
+                    </p>
+                  </div>
+                  <Toggle
+                    checked={localConfig.audio?.keepLosslessCopy ?? false}
+                    onChange={(checked) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        audio: { ...localConfig.audio, keepLosslessCopy: checked },
+                      })
+                    }
+                  />
+                </div>
+
+                {localConfig.audio?.keepLosslessCopy && (
+                  <div>
+                    <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                      WAV Storage Limit
+                    </label>
+                    <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                      The oldest copies are deleted once they use more than this
+                    </p>
+                    <select
+                      value={localConfig.audio.losslessRetentionMb ?? 500}
+                      onChange={(e) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          audio: { ...localConfig.audio, losslessRetentionMb: Number(e.target.value) },
+                        })
+                      }
+                      className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                    >
+                      <option value={100}>100 MB</option>
+                      <option value={500}>500 MB</option>
+                      <option value={1000}>1 GB</option>
+                      <option value={5000}>5 GB</option>
+                    </select>
+                  </div>
+                )}
+
+                <div>
+                  <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                    Upload Format
+                  </label>
+                  <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                    WAV is about 15 times larger than Opus but avoids any compression artifacts
+                  </p>
+                  <select
+                    value={localConfig.encoding?.uploadFormat ?? 'ogg'}
+                    onChange={(e) =>
+                      setLocalConfig({
+                        ...localConfig,
+                        encoding: { ...localConfig.encoding, uploadFormat: e.target.value as 'ogg' | 'wav' },
+                      })
+                    }
+                    className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                  >
+                    <option value="ogg">OGG/Opus (compressed)</option>
+                    <option value="wav">WAV (16-bit PCM)</option>
+                  </select>
+                </div>
+
+                {(localConfig.encoding?.uploadFormat ?? 'ogg') === 'ogg' && (
+                  <>
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Opus Bitrate
+                      </label>
+                      <select
+                        value={localConfig.encoding?.bitrateKbps ?? 16}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            encoding: { ...localConfig.encoding, bitrateKbps: Number(e.target.value) },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value={12}>12 kbps</option>
+                        <option value={16}>16 kbps</option>
+                        <option value={24}>24 kbps</option>
+                        <option value={32}>32 kbps</option>
+                        <option value={48}>48 kbps</option>
+                        <option value={64}>64 kbps</option>
+                      </select>
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Opus Frame Duration
+                      </label>
+                      <p className="text-sm text-gray-500 dark:text-gray-400 mb-2">
+                        Longer frames compress speech more efficiently
+                      </p>
+                      <select
+                        value={localConfig.encoding?.frameMs ?? 60}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            encoding: { ...localConfig.encoding, frameMs: Number(e.target.value) },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value={10}>10 ms</option>
+                        <option value={20}>20 ms</option>
+                        <option value={40}>40 ms</option>
+                        <option value={60}>60 ms</option>
+                      </select>
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
+                        Opus Complexity: {localConfig.encoding?.complexity ?? 10}
+                      </label>
+                      <input
+                        type="range"
+                        min={0}
+                        max={10}
+                        step={1}
+                        value={localConfig.encoding?.complexity ?? 10}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            encoding: { ...localConfig.encoding, complexity: Number(e.target.value) },
+                          })
+                        }
+                        className="w-full accent-amber-500"
+                      />
+                    </div>
+
+                    <div className="flex items-center justify-between">
+                      <div>
+                        <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                          Variable Bitrate
+                        </label>
+                        <p className="text-sm text-gray-500 dark:text-gray-400">
+                          Spend more bits on complex sounds and fewer on pauses
+                        </p>
+                      </div>
+                      <Toggle
+                        checked={localConfig.encoding?.vbr ?? true}
+                        onChange={(checked) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            encoding: { ...localConfig.encoding, vbr: checked },
+                          })
+                        }
+                      />
+                    </div>
+
+                    <div className="flex items-center justify-between">
+                      <div>
+                        <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                          Forward Error Correction
+                        </label>
+                        <p className="text-sm text-gray-500 dark:text-gray-400">
+                          Embed redundant data so lost packets can be recovered (streaming uploads)
+                        </p>
+                      </div>
+                      <Toggle
+                        checked={localConfig.encoding?.fec ?? false}
+                        onChange={(checked) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            encoding: { ...localConfig.encoding, fec: checked },
+                          })
+                        }
+                      />
+                    </div>
+                  </>
+                )}
+
                 <div>
                   <label className="block text-sm font-medium mb-1 text-gray-700 dark:text-gray-300">
                     Speech Languages
@@ -343,6 +1118,76 @@ export const ConfigPage: React.FC = () => {
                     Default: Ctrl + Shift + Z (Press to activate/deactivate recording)
                   </p>
                 </div>
+
+                <div>
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <label className="block text-sm font-medium text-gray-700 dark:text-gray-300">
+                        Pause Hotkey
+                      </label>
+                      <p className="text-sm text-gray-500 dark:text-gray-400">
+                        Pause and resume without ending the recording. Must differ from the recording hotkey
+                      </p>
+                    </div>
+                    <Toggle
+                      checked={localConfig.pauseHotkey !== null}
+                      onChange={(checked) =>
+                        setLocalConfig({
+                          ...localConfig,
+                          pauseHotkey: checked ? DEFAULT_PAUSE_HOTKEY : null,
+                        })
+                      }
+                    />
+                  </div>
+
+                  {localConfig.pauseHotkey && (
+                    <div className="grid grid-cols-3 gap-4 mt-2">
+                      <select
+                        value={localConfig.pauseHotkey.modifier1}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            pauseHotkey: { ...localConfig.pauseHotkey!, modifier1: e.target.value },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        {HOTKEY_MODIFIERS.map((modifier) => (
+                          <option key={modifier} value={modifier}>{modifier}</option>
+                        ))}
+                      </select>
+                      <select
+                        value={localConfig.pauseHotkey.modifier2 ?? ''}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            pauseHotkey: { ...localConfig.pauseHotkey!, modifier2: e.target.value || undefined },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        <option value="">(none)</option>
+                        {HOTKEY_MODIFIERS.map((modifier) => (
+                          <option key={modifier} value={modifier}>{modifier}</option>
+                        ))}
+                      </select>
+                      <select
+                        value={localConfig.pauseHotkey.key}
+                        onChange={(e) =>
+                          setLocalConfig({
+                            ...localConfig,
+                            pauseHotkey: { ...localConfig.pauseHotkey!, key: e.target.value },
+                          })
+                        }
+                        className="w-full px-3 py-2 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-amber-500 cursor-pointer"
+                      >
+                        {HOTKEY_KEYS.map((key) => (
+                          <option key={key} value={key}>{key}</option>
+                        ))}
+                      </select>
+                    </div>
+                  )}
+                </div>
               </div>
             </div>
           </div>
@@ -1,8 +1,54 @@
 import { useState, useRef } from 'react';
-import { Clock, Copy, Check, Trash2, Sparkles, Play, Square, Download } from 'lucide-react';
-import { save } from '@tauri-apps/plugin-dialog';
+import { Clock, Copy, Check, Trash2, Sparkles, Play, Square, Download, FileAudio, FolderOpen } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import { revealItemInDir } from '@tauri-apps/plugin-opener';
 import { writeFile } from '@tauri-apps/plugin-fs';
 import { useTranscriptionHistory } from '../../hooks/useTranscriptionHistory';
+import type { TranscriptionHistoryItem } from '../../store/appStore';
+import type { TranscriptSegment, TranscriptWord } from '../../types/api';
+
+// Badge text for recordings that didn't come from the microphone alone
+const SOURCE_LABELS: Record<string, string> = {
+  system: 'System audio',
+  'microphone+system': 'Mic + system',
+  file: 'File',
+};
+
+// Words recognized with less confidence than this are highlighted
+const LOW_CONFIDENCE = 0.6;
+
+// A run of the original text, tied to a recognized word if it is one
+interface TextPiece {
+  text: string;
+  word?: TranscriptWord & { confidence: number | null };
+}
+
+// Split the original text into words with timing and the text around them.
+// Words are matched in order against the display text, which keeps its
+// punctuation and casing; words that differ after formatting (e.g. "twenty"
+// shown as "20") stay plain text.
+function splitWords(text: string, segments: TranscriptSegment[]): TextPiece[] {
+  const pieces: TextPiece[] = [];
+  const lower = text.toLowerCase();
+  let cursor = 0;
+  for (const segment of segments) {
+    for (const word of segment.words) {
+      const index = lower.indexOf(word.text.toLowerCase(), cursor);
+      if (!word.text || index < 0 || index - cursor > 20) continue;
+      if (index > cursor) pieces.push({ text: text.slice(cursor, index) });
+      const end = index + word.text.length;
+      pieces.push({
+        text: text.slice(index, end),
+        // Fast Transcription only rates whole phrases
+        word: { ...word, confidence: word.confidence ?? segment.confidence },
+      });
+      cursor = end;
+    }
+  }
+  if (cursor < text.length) pieces.push({ text: text.slice(cursor) });
+  return pieces;
+}
 
 function formatTimestamp(timestamp: number): string {
   const date = new Date(timestamp);
@@ -19,9 +65,16 @@ function formatTimestamp(timestamp: number): string {
   return date.toLocaleDateString();
 }
 
+// Uploads are OGG/Opus unless the WAV upload format is selected ("RIFF" header)
+function isWav(audioData: number[]): boolean {
+  return audioData[0] === 0x52 && audioData[1] === 0x49 && audioData[2] === 0x46 && audioData[3] === 0x46;
+}
+
 export const TranscriptionHistory: React.FC = () => {
-  const { transcriptionHistory, clearHistory } = useTranscriptionHistory();
+  const { transcriptionHistory, addToHistory, clearHistory } = useTranscriptionHistory();
   const [copiedKey, setCopiedKey] = useState<string | null>(null);
+  const [isTranscribingFile, setIsTranscribingFile] = useState(false);
+  const [fileError, setFileError] = useState<string | null>(null);
   const [playingKey, setPlayingKey] = useState<number | null>(null);
   const audioRef = useRef<HTMLAudioElement | null>(null);
 
@@ -35,7 +88,7 @@ export const TranscriptionHistory: React.FC = () => {
     }
   };
 
-  const handlePlayAudio = (audioData: number[], timestamp: number) => {
+  const handlePlayAudio = (audioData: number[], timestamp: number, startSecs = 0) => {
     // If clicking the same item that's playing, just stop
     if (playingKey === timestamp) {
       if (audioRef.current) {
@@ -53,10 +106,13 @@ export const TranscriptionHistory: React.FC = () => {
 
     // Convert number array to Uint8Array and create blob
     const uint8Array = new Uint8Array(audioData);
-    const blob = new Blob([uint8Array], { type: 'audio/ogg; codecs=opus' });
+    const blob = new Blob([uint8Array], {
+      type: isWav(audioData) ? 'audio/wav' : 'audio/ogg; codecs=opus',
+    });
     const url = URL.createObjectURL(blob);
 
     const audio = new Audio(url);
+    audio.currentTime = startSecs;
     audioRef.current = audio;
     setPlayingKey(timestamp);
 
@@ -81,6 +137,43 @@ export const TranscriptionHistory: React.FC = () => {
     });
   };
 
+  // Play from a word, or jump to it if this recording is already playing
+  const handleSeekAudio = (audioData: number[], timestamp: number, offsetMs: number) => {
+    if (playingKey === timestamp && audioRef.current) {
+      audioRef.current.currentTime = offsetMs / 1000;
+      return;
+    }
+    handlePlayAudio(audioData, timestamp, offsetMs / 1000);
+  };
+
+  const renderOriginal = (item: TranscriptionHistoryItem) => {
+    if (!item.segments?.some((segment) => segment.words.length > 0)) {
+      return item.original;
+    }
+
+    const canSeek = !!item.audioData && item.audioData.length > 0;
+    return splitWords(item.original, item.segments).map((piece, index) => {
+      const word = piece.word;
+      if (!word) return piece.text;
+
+      const lowConfidence = word.confidence !== null && word.confidence < LOW_CONFIDENCE;
+      const title = `${(word.offset_ms / 1000).toFixed(1)}s` +
+        (word.confidence !== null ? ` · ${Math.round(word.confidence * 100)}% confidence` : '');
+      return (
+        <span
+          key={index}
+          onClick={canSeek ? () => handleSeekAudio(item.audioData!, item.timestamp, word.offset_ms) : undefined}
+          className={`rounded ${
+            lowConfidence ? 'bg-amber-100 text-amber-900 dark:bg-amber-900/40 dark:text-amber-200' : ''
+          } ${canSeek ? 'cursor-pointer hover:bg-blue-100 dark:hover:bg-blue-900/40' : ''}`}
+          title={title}
+        >
+          {piece.text}
+        </span>
+      );
+    });
+  };
+
   const handleStopAudio = () => {
     if (audioRef.current) {
       audioRef.current.pause();
@@ -92,13 +185,14 @@ export const TranscriptionHistory: React.FC = () => {
   const handleSaveAudio = async (audioData: number[], timestamp: number) => {
     // Create filename with timestamp
     const date = new Date(timestamp);
-    const defaultFilename = `fluxvoice_${date.getFullYear()}${String(date.getMonth() + 1).padStart(2, '0')}${String(date.getDate()).padStart(2, '0')}_${String(date.getHours()).padStart(2, '0')}${String(date.getMinutes()).padStart(2, '0')}${String(date.getSeconds()).padStart(2, '0')}.ogg`;
+    const extension = isWav(audioData) ? 'wav' : 'ogg';
+    const defaultFilename = `fluxvoice_${date.getFullYear()}${String(date.getMonth() + 1).padStart(2, '0')}${String(date.getDate()).padStart(2, '0')}_${String(date.getHours()).padStart(2, '0')}${String(date.getMinutes()).padStart(2, '0')}${String(date.getSeconds()).padStart(2, '0')}.${extension}`;
 
     try {
       // Open save dialog
       const filePath = await save({
         defaultPath: defaultFilename,
-        filters: [{ name: 'Audio', extensions: ['ogg'] }],
+        filters: [{ name: 'Audio', extensions: [extension] }],
       });
 
       if (filePath) {
@@ -111,6 +205,59 @@ export const TranscriptionHistory: React.FC = () => {
     }
   };
 
+  const handleTranscribeFile = async () => {
+    try {
+      const path = await open({
+        multiple: false,
+        filters: [{ name: 'Audio', extensions: ['wav', 'flac', 'mp3', 'ogg', 'opus'] }],
+      });
+      if (!path) return;
+
+      setFileError(null);
+      setIsTranscribingFile(true);
+      const result = await invoke<{
+        original: string;
+        polished: string | null;
+        final_text: string;
+        source: string | null;
+        segments: TranscriptSegment[];
+      }>('transcribe_file', { path, insert: false });
+      if (result.final_text && result.final_text.trim()) {
+        await addToHistory(result.original, result.polished, result.final_text, undefined, result.source ?? undefined, result.segments);
+      }
+    } catch (err) {
+      console.error('Failed to transcribe file:', err);
+      setFileError(err as string);
+    } finally {
+      setIsTranscribingFile(false);
+    }
+  };
+
+  const transcribeFileButton = (
+    <button
+      onClick={handleTranscribeFile}
+      disabled={isTranscribingFile}
+      className="flex items-center gap-1 px-3 py-1.5 text-sm text-gray-600 dark:text-gray-300 hover:bg-gray-100 dark:hover:bg-gray-700 rounded-lg transition-colors disabled:opacity-50"
+      title="Transcribe a WAV, FLAC, MP3 or OGG file"
+    >
+      <FileAudio className="w-4 h-4" />
+      {isTranscribingFile ? 'Transcribing...' : 'Transcribe File'}
+    </button>
+  );
+
+  const fileErrorMessage = fileError && (
+    <p className="text-sm text-red-600 dark:text-red-400 mb-4">{fileError}</p>
+  );
+
+  const handleShowWav = async (path: string) => {
+    try {
+      await revealItemInDir(path);
+    } catch (err) {
+      // The copy may have been removed by the retention limit
+      console.error('Failed to show WAV copy:', err);
+    }
+  };
+
   const handleClearHistory = () => {
     if (confirm('Clear all transcription history?')) {
       clearHistory();
@@ -124,7 +271,9 @@ export const TranscriptionHistory: React.FC = () => {
           <Clock className="w-16 h-16 mx-auto mb-4 opacity-50" />
           <p className="text-lg font-medium">No transcriptions yet</p>
           <p className="text-sm mt-2">Your recent transcriptions will appear here</p>
+          <div className="flex justify-center mt-4">{transcribeFileButton}</div>
         </div>
+        {fileErrorMessage}
       </div>
     );
   }
@@ -135,14 +284,18 @@ export const TranscriptionHistory: React.FC = () => {
         <p className="text-sm text-gray-500 dark:text-gray-400">
           {transcriptionHistory.length} transcription{transcriptionHistory.length !== 1 ? 's' : ''}
         </p>
-        <button
-          onClick={handleClearHistory}
-          className="flex items-center gap-1 px-3 py-1.5 text-sm text-red-600 hover:bg-red-50 dark:hover:bg-red-900/20 rounded-lg transition-colors"
-        >
-          <Trash2 className="w-4 h-4" />
-          Clear All
-        </button>
+        <div className="flex items-center gap-2">
+          {transcribeFileButton}
+          <button
+            onClick={handleClearHistory}
+            className="flex items-center gap-1 px-3 py-1.5 text-sm text-red-600 hover:bg-red-50 dark:hover:bg-red-900/20 rounded-lg transition-colors"
+          >
+            <Trash2 className="w-4 h-4" />
+            Clear All
+          </button>
+        </div>
       </div>
+      {fileErrorMessage}
       <div className="space-y-4 flex-1 overflow-y-auto min-h-0">
         {transcriptionHistory.map((item) => (
           <div
@@ -156,7 +309,7 @@ export const TranscriptionHistory: React.FC = () => {
                   <span className="text-xs font-medium text-gray-500 dark:text-gray-400">Original</span>
                 </div>
                 <p className="text-sm text-gray-700 dark:text-gray-300 break-words">
-                  {item.original}
+                  {renderOriginal(item)}
                 </p>
               </div>
               <button
@@ -200,9 +353,16 @@ export const TranscriptionHistory: React.FC = () => {
 
             {/* Timestamp and playback */}
             <div className="flex items-center justify-between mt-2">
-              <p className="text-xs text-gray-500 dark:text-gray-400">
-                {formatTimestamp(item.timestamp)}
-              </p>
+              <div className="flex items-center gap-2">
+                <p className="text-xs text-gray-500 dark:text-gray-400">
+                  {formatTimestamp(item.timestamp)}
+                </p>
+                {item.source && item.source !== 'microphone' && (
+                  <span className="px-1.5 py-0.5 text-[10px] rounded bg-gray-200 text-gray-600 dark:bg-gray-600 dark:text-gray-300">
+                    {SOURCE_LABELS[item.source] ?? item.source}
+                  </span>
+                )}
+              </div>
               {item.audioData && item.audioData.length > 0 && (
                 <div className="flex items-center gap-2">
                   <button
@@ -238,6 +398,16 @@ export const TranscriptionHistory: React.FC = () => {
                     <Download className="w-3 h-3" />
                     <span>Save</span>
                   </button>
+                  {item.recordingPath && (
+                    <button
+                      onClick={() => handleShowWav(item.recordingPath!)}
+                      className="flex items-center gap-1.5 px-2.5 py-1 text-xs rounded-full bg-gray-100 text-gray-600 hover:bg-gray-200 dark:bg-gray-600 dark:text-gray-300 dark:hover:bg-gray-500 transition-colors"
+                      title={item.recordingPath}
+                    >
+                      <FolderOpen className="w-3 h-3" />
+                      <span>WAV</span>
+                    </button>
+                  )}
                 </div>
               )}
             </div>
@@ -26,13 +26,15 @@ const MODE_COLORS: Record<string, string> = {
 
 export const FloatingWindow: React.FC = () => {
   const { recordingState } = useAppStore();
-  const { startRecording, stopRecording } = useAudioRecording();
+  const { startRecording, stopRecording, togglePause } = useAudioRecording();
   const recordingStateRef = useRef(recordingState);
   const isProcessingHotkey = useRef(false);
   const pendingRelease = useRef(false);
   const lastActionTime = useRef(0); // Debounce protection
   const [postProcessingMode, setPostProcessingMode] = useState<'none' | 'polish' | 'translate'>('none');
   const [translateTargetLanguage, setTranslateTargetLanguage] = useState<string>('English');
+  // Hands-free: a press toggles recording and releases are ignored
+  const handsFree = useRef(false);
   const [appVersion, setAppVersion] = useState<string>('');
 
   // Load app version on mount
@@ -46,6 +48,7 @@ export const FloatingWindow: React.FC = () => {
       const config = await invoke<AppConfig>('get_config');
       setPostProcessingMode(config.features.postProcessingMode);
       setTranslateTargetLanguage(config.features.translateTargetLanguage);
+      handsFree.current = config.audio?.handsFree ?? false;
     } catch (err) {
       console.error('Failed to load config for mode:', err);
     }
@@ -120,6 +123,13 @@ export const FloatingWindow: React.FC = () => {
       const currentState = recordingStateRef.current;
       console.log('Hotkey pressed, current state:', currentState);
 
+      // In hands-free mode a second press ends the recording early
+      if (handsFree.current && (currentState === 'recording' || currentState === 'paused')) {
+        lastActionTime.current = now;
+        await stopRecording();
+        return;
+      }
+
       // Only start if idle - not if processing or already recording
       if (currentState === 'idle') {
         isProcessingHotkey.current = true;
@@ -137,8 +147,12 @@ export const FloatingWindow: React.FC = () => {
     const unlistenRelease = listen('hotkey-released', async () => {
       const currentState = recordingStateRef.current;
       console.log('Hotkey released, current state:', currentState);
+      if (handsFree.current) {
+        // The recording stops itself after trailing silence
+        return;
+      }
 
-      if (currentState === 'recording') {
+      if (currentState === 'recording' || currentState === 'paused') {
         lastActionTime.current = Date.now();
         await stopRecording();
       } else if (isProcessingHotkey.current) {
@@ -148,11 +162,48 @@ export const FloatingWindow: React.FC = () => {
       }
     });
 
+    // Backend stopped capturing at the duration/memory limit, or after trailing silence in
+    // hands-free mode - transcribe what we have
+    const unlistenLimit = listen('recording-limit-reached', async () => {
+      console.log('Recording limit reached, current state:', recordingStateRef.current);
+      if (recordingStateRef.current === 'recording' || recordingStateRef.current === 'paused') {
+        lastActionTime.current = Date.now();
+        await stopRecording();
+      }
+    });
+
+    // Microphone failed mid-recording. If capture moved to another device just keep going,
+    // otherwise the backend already stopped - transcribe the audio captured before the failure
+    const unlistenError = listen<{ message: string; recovered: boolean; device: string | null }>(
+      'recording-error',
+      async (event) => {
+        const { message, recovered, device } = event.payload;
+        if (recovered) {
+          console.warn(`Recording error: ${message} - continuing on ${device}`);
+          return;
+        }
+        console.error('Recording error:', message, 'current state:', recordingStateRef.current);
+        if (recordingStateRef.current === 'recording' || recordingStateRef.current === 'paused') {
+          lastActionTime.current = Date.now();
+          await stopRecording();
+        }
+      }
+    );
+
+    // Pause hotkey - pause or resume without ending the recording
+    const unlistenPause = listen('pause-hotkey-pressed', async () => {
+      console.log('Pause hotkey pressed, current state:', recordingStateRef.current);
+      await togglePause();
+    });
+
     return () => {
       unlistenPress.then((fn) => fn());
       unlistenRelease.then((fn) => fn());
+      unlistenLimit.then((fn) => fn());
+      unlistenError.then((fn) => fn());
+      unlistenPause.then((fn) => fn());
     };
-  }, [startRecording, stopRecording]);
+  }, [startRecording, stopRecording, togglePause]);
 
   const handleClick = async () => {
     // Open configuration window
@@ -1,5 +1,5 @@
 import React from 'react';
-import { Mic, AlertCircle, Upload, Zap } from 'lucide-react';
+import { Mic, AlertCircle, AlertTriangle, Pause, Upload, Zap } from 'lucide-react';
 import { useAppStore } from '../../store/appStore';
 
 // Format bytes to human readable string
@@ -16,18 +16,31 @@ function formatDuration(seconds: number): string {
   return `${mins}:${secs.toString().padStart(2, '0')}`;
 }
 
+// Last characters of the streaming partial result, the part that is still changing
+const PARTIAL_TAIL_CHARS = 48;
+
+function partialTail(text: string): string {
+  return text.length > PARTIAL_TAIL_CHARS ? `…${text.slice(-PARTIAL_TAIL_CHARS)}` : text;
+}
+
 export const StatusIndicator: React.FC = () => {
-  const { recordingState, error, uploadSize, recordingDuration } = useAppStore();
+  const { recordingState, error, warning, uploadSize, recordingDuration, silenceCountdown, partialTranscript } = useAppStore();
+  const showWarning = recordingState === 'idle' && !!warning;
 
   const getIcon = () => {
     switch (recordingState) {
       case 'recording':
         return <Mic className="w-4 h-4 text-red-500 animate-pulse" />;
+      case 'paused':
+        return <Pause className="w-4 h-4 text-amber-500" />;
       case 'processing':
         return <Upload className="w-4 h-4 text-blue-500 animate-pulse" />;
       case 'error':
         return <AlertCircle className="w-4 h-4 text-red-500" />;
       default:
+        if (showWarning) {
+          return <AlertTriangle className="w-4 h-4 text-amber-500" />;
+        }
         return (
           <div className="relative">
             <Zap className="w-4 h-4 text-amber-500 animate-pulse" />
@@ -42,12 +55,26 @@ export const StatusIndicator: React.FC = () => {
   const getLabel = () => {
     switch (recordingState) {
       case 'recording':
+        if (silenceCountdown !== null) {
+          return `Recording ${formatDuration(recordingDuration)} · stops in ${(silenceCountdown / 1000).toFixed(1)}s`;
+        }
+        if (partialTranscript) {
+          return partialTail(partialTranscript);
+        }
         return `Recording ${formatDuration(recordingDuration)}`;
+      case 'paused':
+        return `Paused ${formatDuration(recordingDuration)}`;
       case 'processing':
+        if (partialTranscript) {
+          return partialTail(partialTranscript);
+        }
         return uploadSize ? `Uploading ${formatBytes(uploadSize)}...` : 'Processing...';
       case 'error':
         return error ? `Error: ${error}` : 'Error';
       default:
+        if (showWarning) {
+          return warning;
+        }
         return (
           <span className="font-bold bg-gradient-to-r from-amber-500 via-orange-500 to-red-500 bg-clip-text text-transparent animate-pulse">
             FluxVoice
@@ -61,7 +88,7 @@ export const StatusIndicator: React.FC = () => {
       <div className="flex-shrink-0">{getIcon()}</div>
       <span
         className="text-xs font-medium text-gray-700 dark:text-gray-300 truncate"
-        title={recordingState === 'error' && error ? error : undefined}
+        title={recordingState === 'error' && error ? error : showWarning ? warning! : partialTranscript || undefined}
       >
         {getLabel()}
       </span>
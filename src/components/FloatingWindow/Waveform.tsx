@@ -3,8 +3,8 @@ import { useAppStore } from '../../store/appStore';
 import { useWaveform } from '../../hooks/useWaveform';
 
 export const Waveform: React.FC = () => {
-  const { audioLevel, recordingState } = useAppStore();
-  const canvasRef = useWaveform(audioLevel, recordingState === 'recording');
+  const { audioLevel, audioBands, audioClipping, recordingState } = useAppStore();
+  const canvasRef = useWaveform(audioLevel, audioBands, audioClipping, recordingState === 'recording');
 
   return (
     <div className="w-full h-full flex items-center justify-center">
@@ -1,10 +1,45 @@
-import { useState, useCallback } from 'react';
+import { useState, useCallback, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAppStore } from '../store/appStore';
+import type { TranscriptSegment } from '../types/api';
+
+// Must match vad::NO_SPEECH_DETECTED in the backend
+const NO_SPEECH_DETECTED = 'No speech detected';
+
+// Payload of the backend's audio-level event (see audio::meter::AudioLevels)
+interface AudioLevels {
+  rms_db: number;
+  peak_db: number;
+  clipping: boolean;
+  level: number;
+  bands: number[];
+}
+
+// Payload of the backend's recording-paused event (see audio::events::PauseState)
+interface PauseState {
+  paused: boolean;
+  duration_secs: number;
+}
+
+// Payload of the backend's silence-countdown event (see audio::events::SilenceCountdown)
+interface SilenceCountdown {
+  remaining_ms: number | null;
+  timeout_ms: number;
+}
+
+// Payload of the backend's transcription-partial event (see azure::speech_stream::PartialTranscript)
+interface PartialTranscript {
+  text: string;
+}
 
 // Global lock to prevent concurrent operations
 let isOperationInProgress = false;
 
+// The duration timer counts from timerOrigin and stands still while paused
+let timerOrigin = 0;
+let pausedAt: number | null = null;
+
 // Play a short beep sound using Web Audio API
 function playStartSound() {
   try {
@@ -33,16 +68,79 @@ export function useAudioRecording() {
     recordingDuration,
     setRecordingState,
     setAudioLevel,
+    setAudioMeter,
+    setSilenceCountdown,
+    setPartialTranscript,
     setTranscription,
     setError,
+    setWarning,
     setUploadSize,
     setRecordingStartTime,
     setRecordingDuration,
     addToHistory,
   } = useAppStore();
-  const [intervalId, setIntervalId] = useState<number | null>(null);
   const [durationIntervalId, setDurationIntervalId] = useState<number | null>(null);
 
+  // The capture thread publishes levels about 20 times per second while recording
+  useEffect(() => {
+    const unlisten = listen<AudioLevels>('audio-level', (event) => {
+      const { level, bands, clipping } = event.payload;
+      setAudioMeter(level, bands, clipping);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [setAudioMeter]);
+
+  // Hands-free: trailing silence is counting down to an automatic stop (null = speech resumed)
+  useEffect(() => {
+    const unlisten = listen<SilenceCountdown>('silence-countdown', (event) => {
+      if (useAppStore.getState().recordingState !== 'recording') return;
+      setSilenceCountdown(event.payload.remaining_ms);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [setSilenceCountdown]);
+
+  // Streaming recognition: partial results while recording and until the final one arrives
+  useEffect(() => {
+    const unlisten = listen<PartialTranscript>('transcription-partial', (event) => {
+      if (useAppStore.getState().recordingState === 'idle') return;
+      setPartialTranscript(event.payload.text);
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [setPartialTranscript]);
+
+  // The backend confirms pauses, whether they came from a button or the pause hotkey
+  useEffect(() => {
+    const unlisten = listen<PauseState>('recording-paused', (event) => {
+      const { paused, duration_secs } = event.payload;
+      const state = useAppStore.getState().recordingState;
+      if (state !== 'recording' && state !== 'paused') return;
+
+      console.log(`[useAudioRecording] Recording ${paused ? 'paused' : 'resumed'} at ${duration_secs.toFixed(1)}s`);
+      if (paused) {
+        pausedAt = pausedAt ?? Date.now();
+        setAudioMeter(0, [], false);
+        setSilenceCountdown(null);
+      } else if (pausedAt !== null) {
+        timerOrigin += Date.now() - pausedAt;
+        pausedAt = null;
+      }
+      setRecordingState(paused ? 'paused' : 'recording');
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [setAudioMeter, setSilenceCountdown, setRecordingState]);
+
   const startRecording = useCallback(async () => {
     // Prevent concurrent operations
     if (isOperationInProgress) {
@@ -53,10 +151,6 @@ export function useAudioRecording() {
     console.log('[useAudioRecording] startRecording - starting...');
 
     // Clear any leftover intervals from previous recording
-    if (intervalId) {
-      clearInterval(intervalId);
-      setIntervalId(null);
-    }
     if (durationIntervalId) {
       clearInterval(durationIntervalId);
       setDurationIntervalId(null);
@@ -64,35 +158,29 @@ export function useAudioRecording() {
 
     try {
       setError(null);
+      setWarning(null);
       setUploadSize(null);
       setRecordingDuration(0);
-      setAudioLevel(0);
+      setAudioMeter(0, [], false);
+      setSilenceCountdown(null);
+      setPartialTranscript('');
 
       // Play start sound
       playStartSound();
 
       const startTime = Date.now();
+      timerOrigin = startTime;
+      pausedAt = null;
       setRecordingStartTime(startTime);
       setRecordingState('recording');
       console.log('[useAudioRecording] Calling invoke start_recording...');
       await invoke('start_recording');
       console.log('[useAudioRecording] start_recording returned successfully');
 
-      // Start polling audio level for waveform
-      const id = window.setInterval(async () => {
-        try {
-          const level = await invoke<number>('get_audio_level');
-          setAudioLevel(level);
-        } catch (err) {
-          console.error('[useAudioRecording] Failed to get audio level:', err);
-        }
-      }, 50); // Update 20 times per second
-
-      setIntervalId(id);
-
       // Start duration timer
       const durationId = window.setInterval(() => {
-        const elapsed = Math.floor((Date.now() - startTime) / 1000);
+        if (pausedAt !== null) return;
+        const elapsed = Math.floor((Date.now() - timerOrigin) / 1000);
         setRecordingDuration(elapsed);
       }, 100);
 
@@ -110,7 +198,7 @@ export function useAudioRecording() {
       isOperationInProgress = false;
       console.log('[useAudioRecording] startRecording - finished');
     }
-  }, [intervalId, durationIntervalId, setRecordingState, setAudioLevel, setError, setUploadSize, setRecordingStartTime, setRecordingDuration]);
+  }, [durationIntervalId, setRecordingState, setAudioMeter, setSilenceCountdown, setPartialTranscript, setError, setWarning, setUploadSize, setRecordingStartTime, setRecordingDuration]);
 
   const stopRecording = useCallback(async () => {
     // Prevent multiple stop calls
@@ -126,10 +214,6 @@ export function useAudioRecording() {
     console.log('[useAudioRecording] Captured duration:', capturedDuration, 'seconds');
 
     // Always clear intervals first
-    if (intervalId) {
-      clearInterval(intervalId);
-      setIntervalId(null);
-    }
     if (durationIntervalId) {
       clearInterval(durationIntervalId);
       setDurationIntervalId(null);
@@ -137,6 +221,7 @@ export function useAudioRecording() {
 
     setRecordingState('processing');
     setRecordingStartTime(null);
+    setSilenceCountdown(null);
 
     let audioData: number[] | null = null;
 
@@ -150,12 +235,18 @@ export function useAudioRecording() {
         setUploadSize(audioData.length);
       }
     } catch (err) {
-      console.error('[useAudioRecording] Failed to stop recording:', err);
-      setError(err as string);
+      if (err === NO_SPEECH_DETECTED) {
+        console.log('[useAudioRecording] No speech detected - nothing sent for transcription');
+        setError('No speech detected - nothing was sent');
+      } else {
+        console.error('[useAudioRecording] Failed to stop recording:', err);
+        setError(err as string);
+      }
       setRecordingState('idle'); // Immediately return to idle so user can retry
       setAudioLevel(0);
       setUploadSize(null);
       setRecordingDuration(0);
+      setPartialTranscript('');
       isOperationInProgress = false;
       // Clear error message after 3 seconds
       setTimeout(() => {
@@ -173,13 +264,21 @@ export function useAudioRecording() {
         final_text: string;
         post_processing_mode: string;
         warning: string | null;
+        recording_path: string | null;
+        source: string | null;
+        segments: TranscriptSegment[];
       }>('transcribe_and_insert', {
         audioData,
       });
       console.log('[useAudioRecording] Transcription result:', result.original.substring(0, 50) + '...');
       console.log('[useAudioRecording] Post-processing mode:', result.post_processing_mode);
       if (result.warning) {
-        console.warn('[useAudioRecording] Post-processing warning:', result.warning);
+        console.warn('[useAudioRecording] Transcription warning:', result.warning);
+        // Show it after returning to idle, e.g. so users learn their mic gain is off
+        setWarning(result.warning);
+        setTimeout(() => {
+          setWarning(null);
+        }, 6000);
       }
 
       setTranscription(result.final_text);
@@ -187,7 +286,7 @@ export function useAudioRecording() {
       if (result.final_text && result.final_text.trim()) {
         const timestamp = Date.now();
         // Update UI immediately
-        addToHistory(result.original, result.polished, result.final_text, audioData ?? undefined, timestamp);
+        addToHistory(result.original, result.polished, result.final_text, audioData ?? undefined, timestamp, result.recording_path ?? undefined, result.source ?? undefined, result.segments);
         // Save to backend (writes to disk immediately)
         try {
           await invoke('save_history_item', {
@@ -197,6 +296,9 @@ export function useAudioRecording() {
               final_text: result.final_text,
               timestamp,
               audio_data: audioData ?? null,
+              recording_path: result.recording_path,
+              source: result.source,
+              segments: result.segments,
             },
           });
           console.log('[useAudioRecording] History item saved to backend');
@@ -229,16 +331,24 @@ export function useAudioRecording() {
         setError(null);
       }, 3000);
     } finally {
+      setPartialTranscript('');
       isOperationInProgress = false;
     }
-  }, [intervalId, durationIntervalId, recordingDuration, setRecordingState, setTranscription, setAudioLevel, setError, setUploadSize, setRecordingStartTime, setRecordingDuration, addToHistory]);
+  }, [durationIntervalId, recordingDuration, setRecordingState, setSilenceCountdown, setPartialTranscript, setTranscription, setAudioLevel, setError, setWarning, setUploadSize, setRecordingStartTime, setRecordingDuration, addToHistory]);
+
+  // Pause or resume the current recording; the state changes when the backend confirms
+  const togglePause = useCallback(async () => {
+    const state = useAppStore.getState().recordingState;
+    if (state !== 'recording' && state !== 'paused') return;
+    try {
+      await invoke(state === 'recording' ? 'pause_recording' : 'resume_recording');
+    } catch (err) {
+      console.error('[useAudioRecording] Failed to toggle pause:', err);
+    }
+  }, []);
 
   // Reset function to clear state after error recovery
   const resetState = useCallback(() => {
-    if (intervalId) {
-      clearInterval(intervalId);
-      setIntervalId(null);
-    }
     if (durationIntervalId) {
       clearInterval(durationIntervalId);
       setDurationIntervalId(null);
@@ -248,11 +358,12 @@ export function useAudioRecording() {
     setAudioLevel(0);
     setRecordingDuration(0);
     setRecordingStartTime(null);
-  }, [intervalId, durationIntervalId, setRecordingState, setError, setAudioLevel, setRecordingDuration, setRecordingStartTime]);
+  }, [durationIntervalId, setRecordingState, setError, setAudioLevel, setRecordingDuration, setRecordingStartTime]);
 
   return {
     startRecording,
     stopRecording,
+    togglePause,
     resetState,
   };
 }
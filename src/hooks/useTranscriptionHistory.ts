@@ -1,6 +1,8 @@
 import { useEffect, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useAppStore, TranscriptionHistoryItem } from '../store/appStore';
+import type { TranscriptSegment } from '../types/api';
 
 // Backend history item type (uses snake_case)
 interface BackendHistoryItem {
@@ -9,6 +11,9 @@ interface BackendHistoryItem {
   final_text: string;
   timestamp: number;
   audio_data: number[] | null;
+  recording_path?: string | null;
+  source?: string | null;
+  segments?: TranscriptSegment[];
 }
 
 // Convert backend format to frontend format
@@ -19,6 +24,9 @@ function fromBackend(item: BackendHistoryItem): TranscriptionHistoryItem {
     finalText: item.final_text,
     timestamp: item.timestamp,
     audioData: item.audio_data ?? undefined,
+    recordingPath: item.recording_path ?? undefined,
+    source: item.source ?? undefined,
+    segments: item.segments,
   };
 }
 
@@ -30,6 +38,9 @@ function toBackend(item: TranscriptionHistoryItem): BackendHistoryItem {
     final_text: item.finalText,
     timestamp: item.timestamp,
     audio_data: item.audioData ?? null,
+    recording_path: item.recordingPath ?? null,
+    source: item.source ?? null,
+    segments: item.segments ?? [],
   };
 }
 
@@ -56,12 +67,30 @@ export function useTranscriptionHistory() {
     loadHistory();
   }, []);
 
+  // The retention limit deleted some WAV copies, drop the links to them
+  useEffect(() => {
+    const unlistenDeleted = listen<string[]>('recordings-deleted', (event) => {
+      const deleted = new Set(event.payload);
+      useAppStore.setState((state) => ({
+        transcriptionHistory: state.transcriptionHistory.map((item) =>
+          item.recordingPath && deleted.has(item.recordingPath) ? { ...item, recordingPath: undefined } : item
+        ),
+      }));
+    });
+
+    return () => {
+      unlistenDeleted.then((fn) => fn());
+    };
+  }, []);
+
   // Add to history and save to backend immediately
   const addToHistory = useCallback(async (
     original: string,
     polished: string | null,
     finalText: string,
-    audioData?: number[]
+    audioData?: number[],
+    source?: string,
+    segments?: TranscriptSegment[]
   ) => {
     const timestamp = Date.now();
 
@@ -71,10 +100,12 @@ export function useTranscriptionHistory() {
       finalText,
       timestamp,
       audioData,
+      source,
+      segments,
     };
 
     // Add to store for immediate UI update
-    storeAddToHistory(original, polished, finalText, audioData, timestamp);
+    storeAddToHistory(original, polished, finalText, audioData, timestamp, undefined, source, segments);
 
     // Save to backend (writes to disk immediately)
     try {
@@ -1,9 +1,20 @@
 import { useRef, useEffect } from 'react';
 
-export function useWaveform(audioLevel: number, isRecording: boolean) {
+// dBFS range mapped onto the bar height
+const BAND_FLOOR_DB = -70;
+const BAND_CEILING_DB = -10;
+// How quickly bars fall back after a peak (fraction per frame)
+const BAR_DECAY = 0.15;
+
+function bandHeight(db: number) {
+  return Math.min(1, Math.max(0, (db - BAND_FLOOR_DB) / (BAND_CEILING_DB - BAND_FLOOR_DB)));
+}
+
+export function useWaveform(audioLevel: number, bands: number[], clipping: boolean, isRecording: boolean) {
   const canvasRef = useRef<HTMLCanvasElement | null>(null);
   const animationRef = useRef<number | undefined>(undefined);
   const historyRef = useRef<number[]>([]);
+  const barsRef = useRef<number[]>([]);
 
   useEffect(() => {
     if (!canvasRef.current) return;
@@ -12,36 +23,55 @@ export function useWaveform(audioLevel: number, isRecording: boolean) {
     const ctx = canvas.getContext('2d');
     if (!ctx) return;
 
-    const bars = 20;
-    const barWidth = canvas.width / bars;
+    const historyBars = 20;
 
     // Initialize history if empty
     if (historyRef.current.length === 0) {
-      historyRef.current = new Array(bars).fill(0);
+      historyRef.current = new Array(historyBars).fill(0);
     }
 
     if (!isRecording) {
       // Clear canvas when not recording
       ctx.clearRect(0, 0, canvas.width, canvas.height);
-      historyRef.current = new Array(bars).fill(0);
+      historyRef.current = new Array(historyBars).fill(0);
+      barsRef.current = [];
       return;
     }
 
     const animate = () => {
-      // Shift history
-      historyRef.current.shift();
-      historyRef.current.push(audioLevel);
+      let targets: number[];
+      if (bands.length > 0) {
+        // Mirror the spectrum so low frequencies sit in the middle
+        const heights = bands.map(bandHeight);
+        targets = [...heights].reverse().concat(heights);
+      } else {
+        // No band data yet, fall back to a scrolling level history
+        historyRef.current.shift();
+        historyRef.current.push(audioLevel);
+        targets = historyRef.current;
+      }
+
+      // Rise immediately, decay smoothly
+      if (barsRef.current.length !== targets.length) {
+        barsRef.current = new Array(targets.length).fill(0);
+      }
+      barsRef.current = barsRef.current.map((current, i) =>
+        Math.max(targets[i], current - BAR_DECAY * current)
+      );
 
       // Clear canvas
       ctx.clearRect(0, 0, canvas.width, canvas.height);
 
       // Draw bars
-      historyRef.current.forEach((level, i) => {
+      const barWidth = canvas.width / barsRef.current.length;
+      barsRef.current.forEach((level, i) => {
         const height = level * canvas.height * 0.8;
         const x = i * barWidth;
         const y = (canvas.height - height) / 2;
 
-        ctx.fillStyle = `rgba(99, 102, 241, ${0.3 + level * 0.7})`;
+        ctx.fillStyle = clipping
+          ? `rgba(239, 68, 68, ${0.3 + level * 0.7})`
+          : `rgba(99, 102, 241, ${0.3 + level * 0.7})`;
         ctx.fillRect(x + 2, y, barWidth - 4, height);
       });
 
@@ -55,7 +85,7 @@ export function useWaveform(audioLevel: number, isRecording: boolean) {
         cancelAnimationFrame(animationRef.current);
       }
     };
-  }, [audioLevel, isRecording]);
+  }, [audioLevel, bands, clipping, isRecording]);
 
   return canvasRef;
 }
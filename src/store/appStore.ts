@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import type { AppConfig, RecordingState } from '../types/config';
+import type { TranscriptSegment } from '../types/api';
 
 export interface TranscriptionHistoryItem {
   original: string;
@@ -7,15 +8,23 @@ export interface TranscriptionHistoryItem {
   finalText: string;
   timestamp: number;
   audioData?: number[]; // Opus/OGG audio data for playback
+  recordingPath?: string; // Lossless WAV copy on disk, if one was kept
+  source?: string; // 'microphone', 'system', 'microphone+system' or 'file'
+  segments?: TranscriptSegment[]; // Phrase/word timing of the original, if the engine gave any
 }
 
 interface AppStore {
   // State
   recordingState: RecordingState;
   audioLevel: number;
+  audioBands: number[]; // Band energies in dBFS, low to high, from the latest audio-level event
+  audioClipping: boolean;
+  silenceCountdown: number | null; // Hands-free: ms until trailing silence stops the recording
+  partialTranscript: string; // Streaming: text recognized so far in the current recording
   transcription: string;
   config: AppConfig | null;
   error: string | null;
+  warning: string | null; // Non-fatal issue with the last recording (e.g. mic gain)
   uploadSize: number | null; // Size of audio data being uploaded in bytes
   recordingStartTime: number | null; // Timestamp when recording started
   recordingDuration: number; // Current recording duration in seconds
@@ -24,22 +33,31 @@ interface AppStore {
   // Actions
   setRecordingState: (state: RecordingState) => void;
   setAudioLevel: (level: number) => void;
+  setAudioMeter: (level: number, bands: number[], clipping: boolean) => void;
+  setSilenceCountdown: (remainingMs: number | null) => void;
+  setPartialTranscript: (text: string) => void;
   setTranscription: (text: string) => void;
   setConfig: (config: AppConfig) => void;
   setError: (error: string | null) => void;
+  setWarning: (warning: string | null) => void;
   setUploadSize: (size: number | null) => void;
   setRecordingStartTime: (time: number | null) => void;
   setRecordingDuration: (duration: number) => void;
-  addToHistory: (original: string, polished: string | null, finalText: string, audioData?: number[], timestamp?: number) => void;
+  addToHistory: (original: string, polished: string | null, finalText: string, audioData?: number[], timestamp?: number, recordingPath?: string, source?: string, segments?: TranscriptSegment[]) => void;
   clearHistory: () => void;
 }
 
 export const useAppStore = create<AppStore>((set) => ({
   recordingState: 'idle',
   audioLevel: 0,
+  audioBands: [],
+  audioClipping: false,
+  silenceCountdown: null,
+  partialTranscript: '',
   transcription: '',
   config: null,
   error: null,
+  warning: null,
   uploadSize: null,
   recordingStartTime: null,
   recordingDuration: 0,
@@ -47,16 +65,20 @@ export const useAppStore = create<AppStore>((set) => ({
 
   setRecordingState: (state) => set({ recordingState: state }),
   setAudioLevel: (level) => set({ audioLevel: level }),
+  setAudioMeter: (level, bands, clipping) => set({ audioLevel: level, audioBands: bands, audioClipping: clipping }),
+  setSilenceCountdown: (remainingMs) => set({ silenceCountdown: remainingMs }),
+  setPartialTranscript: (text) => set({ partialTranscript: text }),
   setTranscription: (text) => set({ transcription: text }),
   setConfig: (config) => set({ config }),
   setError: (error) => set({ error }),
+  setWarning: (warning) => set({ warning }),
   setUploadSize: (size) => set({ uploadSize: size }),
   setRecordingStartTime: (time) => set({ recordingStartTime: time }),
   setRecordingDuration: (duration) => set({ recordingDuration: duration }),
-  addToHistory: (original, polished, finalText, audioData, timestamp) =>
+  addToHistory: (original, polished, finalText, audioData, timestamp, recordingPath, source, segments) =>
     set((state) => ({
       transcriptionHistory: [
-        { original, polished, finalText, timestamp: timestamp ?? Date.now(), audioData },
+        { original, polished, finalText, timestamp: timestamp ?? Date.now(), audioData, recordingPath, source, segments },
         ...state.transcriptionHistory,
       ],
     })),
@@ -3,6 +3,22 @@ export interface TranscriptionResult {
   polished?: string;
 }
 
+// Phrase and word timing from the speech engine, offsets into the uploaded audio
+export interface TranscriptWord {
+  text: string;
+  offset_ms: number;
+  duration_ms: number;
+  confidence: number | null;
+}
+
+export interface TranscriptSegment {
+  text: string;
+  offset_ms: number;
+  duration_ms: number;
+  confidence: number | null;
+  words: TranscriptWord[];
+}
+
 export interface AudioLevelUpdate {
   level: number;
   timestamp: number;
@@ -1,9 +1,15 @@
 export interface AppConfig {
+  provider: 'azure' | 'openai' | 'whisper'; // Speech-to-text backend
   azure: AzureConfig;
+  whisper: WhisperConfig;
+  openaiTranscription: OpenAiTranscriptionConfig;
   hotkey: HotkeyConfig;
+  pauseHotkey: HotkeyConfig | null; // Pauses/resumes the current recording; null leaves it unbound
   language: LanguageConfig;
   ui: UIConfig;
   features: FeatureConfig;
+  audio: AudioConfig;
+  encoding: EncodingConfig;
 }
 
 export interface AzureConfig {
@@ -12,6 +18,20 @@ export interface AzureConfig {
   openaiEndpoint: string;
   openaiKey: string;
   openaiDeployment: string;
+  streaming: boolean; // Recognize while recording and show partial results
+}
+
+export interface WhisperConfig {
+  modelPath: string; // ggml model file, e.g. ggml-base.en.bin
+  threads: number;   // 0 picks one per CPU core
+}
+
+// Any server with the OpenAI audio/transcriptions API
+export interface OpenAiTranscriptionConfig {
+  baseUrl: string;  // e.g. https://api.openai.com/v1
+  model: string;    // Empty for Azure OpenAI (the deployment picks the model)
+  apiKey: string;
+  language: string; // ISO 639-1 hint; empty uses the speech languages
 }
 
 export interface HotkeyConfig {
@@ -41,4 +61,39 @@ export interface FeatureConfig {
   autoInsertEnabled: boolean;
 }
 
-export type RecordingState = 'idle' | 'recording' | 'processing' | 'error';
+export interface AudioConfig {
+  inputDevice: string | null;  // Device name; null uses the system default
+  captureSource: 'microphone' | 'system' | 'both'; // 'system' records what the computer plays (loopback)
+  loopbackDevice: string | null; // Monitor/loopback device name; null uses the default
+  reconnectOnDisconnect: boolean; // Continue on the system default device if the mic goes away mid-recording
+  channelModes: Record<string, string>; // Device name -> 'average', 'loudest' or 'channel-N' (1-based)
+  trimSilence: boolean;        // Trim leading/trailing silence and skip uploads with no speech
+  warmMicrophone: boolean;     // Keep the mic open between recordings (shown as in use by the OS)
+  preRollMs: number;           // Audio from before the hotkey press kept in warm mode
+  maxRecordingSecs: number;    // Recording stops itself after this long
+  handsFree: boolean;          // One hotkey press starts recording; it stops after trailing silence
+  silenceTimeoutMs: number;    // Silence after speech that ends a hands-free recording
+  handsFreeMaxSecs: number;    // Hands-free recordings stop after this long regardless
+  keepLosslessCopy: boolean;   // Also save a 16 kHz WAV of each recording
+  losslessRetentionMb: number; // Oldest WAV copies are deleted beyond this total size
+  noiseSuppression: boolean;
+  noiseSuppressionStrength: number; // 0.0 (gentle) - 1.0 (aggressive)
+  autoGain: boolean;           // Normalize quiet or loud speech before encoding
+}
+
+export interface EncodingConfig {
+  uploadFormat: 'ogg' | 'wav'; // Opus in OGG, or 16-bit PCM WAV (larger but lossless)
+  bitrateKbps: number;
+  complexity: number;          // 0 (fastest) - 10 (best quality)
+  vbr: boolean;
+  fec: boolean;                // In-band forward error correction
+  frameMs: number;             // 10, 20, 40 or 60
+}
+
+export interface InputDeviceInfo {
+  name: string;
+  is_default: boolean;
+  channels: number; // 0 if unknown
+}
+
+export type RecordingState = 'idle' | 'recording' | 'paused' | 'processing' | 'error';